@@ -1,13 +1,26 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use shared::{AppMutex, MacroConfig, Macro, MacroAction};
+use shared::{convert, dsl, AppMutex, MacroConfig, Macro, MacroAction};
+use shared::gesture::Gesture;
+use shared::layer::Layer;
+use shared::config::{BackupInfo, BundleConflict, ConfigLocation, ExpandError, MacroBundle, Passthrough, Resolution, TriggerConflict};
+use shared::ipc::{self, IpcEvent, IpcRequest};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::BufReader;
+use std::sync::OnceLock;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct AppState {
     config: MacroConfig,
 }
 
+/// 실행 중 한 번만 찾는다 (KEYM_CONFIG → 실행 파일 폴더 → 사용자 설정 폴더)
+fn config_location() -> &'static ConfigLocation {
+    static LOCATION: OnceLock<ConfigLocation> = OnceLock::new();
+    LOCATION.get_or_init(|| shared::config::locate(None))
+}
+
 // 사용 가능한 키 목록
 const AVAILABLE_KEYS: &[&str] = &[
     "0", "1", "2", "3", "4", "5", "6", "7", "8", "9",
@@ -28,17 +41,27 @@ fn get_available_keys() -> Vec<String> {
     AVAILABLE_KEYS.iter().map(|s| s.to_string()).collect()
 }
 
+#[tauri::command]
+fn get_config_location() -> ConfigLocation {
+    config_location().clone()
+}
+
 #[tauri::command]
 fn load_config() -> Result<MacroConfig, String> {
-    let mut config = MacroConfig::load("config.toml")
-        .unwrap_or_default();
+    let location = config_location();
+    // 파일이 없을 때만 새로 시작한다. 읽을 수 없는 파일을 빈 설정으로 덮어쓰지 않도록
+    let mut config = if location.exists {
+        MacroConfig::load(&location.path)
+            .map_err(|e| format!("설정 파일 로드 실패 ({}): {}", location.path.display(), e))?
+    } else {
+        MacroConfig::default()
+    };
     
     // 기본 매크로가 없으면 하나 생성
     if config.macros.is_empty() {
         config.macros.push(Macro {
-            trigger: "1".to_string(),
-            actions: Vec::new(),
             mode: 2,
+            ..Macro::new("1")
         });
     }
     
@@ -47,123 +70,350 @@ fn load_config() -> Result<MacroConfig, String> {
 
 #[tauri::command]
 fn save_config(config: MacroConfig) -> Result<(), String> {
-    config.save("config.toml")
+    // 없는 대상은 include된 파일에 있을 수 있으므로 막지 않는다
+    let errors: Vec<String> = config.check_macros()
+        .into_iter()
+        .filter(|e| !matches!(e, ExpandError::Missing { .. }))
+        .map(|e| e.to_string())
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!("저장 실패: {}", errors.join(", ")));
+    }
+    
+    config.save(&config_location().path)
         .map_err(|e| format!("저장 실패: {}", e))
 }
 
+#[tauri::command]
+fn list_backups() -> Result<Vec<BackupInfo>, String> {
+    MacroConfig::backups(&config_location().path)
+        .map_err(|e| format!("백업 목록 읽기 실패: {}", e))
+}
+
+#[tauri::command]
+fn restore_backup(index: usize) -> Result<MacroConfig, String> {
+    MacroConfig::restore_backup(&config_location().path, index)
+        .map_err(|e| format!("복원 실패: {}", e))
+}
+
+fn find_macro<'a>(config: &'a mut MacroConfig, id: &str) -> Result<&'a mut Macro, String> {
+    config.get_macro_by_id_mut(id)
+        .ok_or_else(|| format!("매크로를 찾을 수 없습니다: {}", id))
+}
+
 #[tauri::command]
 fn add_macro(mut config: MacroConfig) -> Result<MacroConfig, String> {
     config.macros.push(Macro {
-        trigger: "1".to_string(),
-        actions: Vec::new(),
         mode: 2,
+        ..Macro::new("1")
     });
     Ok(config)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 fn update_macro(
     mut config: MacroConfig,
-    index: usize,
+    id: String,
     trigger: String,
     mode: u8,
+    name: String,
+    description: String,
+    tags: Vec<String>,
+    enabled: bool,
+    gesture: Gesture,
+    passthrough: Passthrough,
+    layer: String,
 ) -> Result<MacroConfig, String> {
-    if index < config.macros.len() {
-        config.macros[index].trigger = trigger;
-        config.macros[index].mode = mode;
-        Ok(config)
-    } else {
-        Err("잘못된 매크로 인덱스".to_string())
-    }
+    let macro_item = find_macro(&mut config, &id)?;
+    macro_item.trigger = trigger;
+    macro_item.mode = mode;
+    macro_item.name = name;
+    macro_item.description = description;
+    macro_item.tags = tags;
+    macro_item.enabled = enabled;
+    macro_item.gesture = gesture;
+    macro_item.passthrough = passthrough;
+    macro_item.layer = layer;
+    Ok(config)
 }
 
 #[tauri::command]
-fn delete_macro(mut config: MacroConfig, index: usize) -> Result<MacroConfig, String> {
-    if index < config.macros.len() {
-        config.macros.remove(index);
+fn delete_macro(mut config: MacroConfig, id: String) -> Result<MacroConfig, String> {
+    let before = config.macros.len();
+    config.macros.retain(|m| m.id != id);
+    if config.macros.len() < before {
         Ok(config)
     } else {
-        Err("잘못된 매크로 인덱스".to_string())
+        Err(format!("매크로를 찾을 수 없습니다: {}", id))
     }
 }
 
 #[tauri::command]
 fn add_action(
     mut config: MacroConfig,
-    macro_index: usize,
+    macro_id: String,
     key: String,
     hold_ms: u64,
     delay_ms: u64,
 ) -> Result<MacroConfig, String> {
-    if macro_index < config.macros.len() {
-        config.macros[macro_index].actions.push(MacroAction {
-            key,
-            hold_ms,
-            delay_ms,
-        });
-        Ok(config)
-    } else {
-        Err("잘못된 매크로 인덱스".to_string())
+    find_macro(&mut config, &macro_id)?.actions.push(MacroAction::new(key, hold_ms, delay_ms));
+    Ok(config)
+}
+
+#[tauri::command]
+fn add_call_action(
+    mut config: MacroConfig,
+    macro_id: String,
+    target: String,
+    delay_ms: u64,
+) -> Result<MacroConfig, String> {
+    if config.find_callee(&target).is_none() {
+        return Err(format!("호출할 매크로를 찾을 수 없습니다: {}", target));
     }
+    find_macro(&mut config, &macro_id)?.actions.push(MacroAction::call(target, delay_ms));
+    Ok(config)
 }
 
 #[tauri::command]
 fn update_action(
     mut config: MacroConfig,
-    macro_index: usize,
+    macro_id: String,
     action_index: usize,
     key: String,
     hold_ms: u64,
     delay_ms: u64,
 ) -> Result<MacroConfig, String> {
-    if macro_index < config.macros.len() 
-        && action_index < config.macros[macro_index].actions.len() {
-        let action = &mut config.macros[macro_index].actions[action_index];
-        action.key = key;
-        action.hold_ms = hold_ms;
-        action.delay_ms = delay_ms;
-        Ok(config)
-    } else {
-        Err("잘못된 인덱스".to_string())
-    }
+    let action = find_macro(&mut config, &macro_id)?
+        .actions
+        .get_mut(action_index)
+        .ok_or("잘못된 액션 인덱스")?;
+    // 편집기는 키 액션만 고친다. call, group, repeat, press는 남기지 않는다
+    *action = MacroAction::new(key, hold_ms, delay_ms);
+    Ok(config)
 }
 
 #[tauri::command]
 fn delete_action(
     mut config: MacroConfig,
-    macro_index: usize,
+    macro_id: String,
     action_index: usize,
 ) -> Result<MacroConfig, String> {
-    if macro_index < config.macros.len() 
-        && action_index < config.macros[macro_index].actions.len() {
-        config.macros[macro_index].actions.remove(action_index);
+    let actions = &mut find_macro(&mut config, &macro_id)?.actions;
+    if action_index < actions.len() {
+        actions.remove(action_index);
         Ok(config)
     } else {
-        Err("잘못된 인덱스".to_string())
+        Err("잘못된 액션 인덱스".to_string())
     }
 }
 
 #[tauri::command]
 fn move_action(
     mut config: MacroConfig,
-    macro_index: usize,
+    macro_id: String,
     from_index: usize,
     to_index: usize,
 ) -> Result<MacroConfig, String> {
-    if macro_index < config.macros.len() {
-        let actions = &mut config.macros[macro_index].actions;
-        if from_index < actions.len() && to_index < actions.len() {
-            let action = actions.remove(from_index);
-            actions.insert(to_index, action);
-            Ok(config)
-        } else {
-            Err("잘못된 액션 인덱스".to_string())
-        }
+    let actions = &mut find_macro(&mut config, &macro_id)?.actions;
+    if from_index < actions.len() && to_index < actions.len() {
+        let action = actions.remove(from_index);
+        actions.insert(to_index, action);
+        Ok(config)
     } else {
-        Err("잘못된 매크로 인덱스".to_string())
+        Err("잘못된 액션 인덱스".to_string())
     }
 }
 
+/// 다른 도구용 스크립트로 내보내 설정 파일 옆에 저장한다. 저장한 경로를 돌려준다
+#[tauri::command]
+fn export_config(config: MacroConfig, format: String) -> Result<String, String> {
+    let (content, file_name) = match format.as_str() {
+        "ahk" => (convert::ahk::export(&config), "keym.ahk"),
+        "xdotool" => (convert::shell::export(&config, convert::shell::Tool::Xdotool), "keym-xdotool.sh"),
+        "ydotool" => (convert::shell::export(&config, convert::shell::Tool::Ydotool), "keym-ydotool.sh"),
+        _ => return Err(format!("지원하지 않는 형식: {}", format)),
+    };
+    let path = config_location().path.with_file_name(file_name);
+    std::fs::write(&path, content)
+        .map_err(|e| format!("내보내기 실패 ({}): {}", path.display(), e))?;
+    Ok(path.display().to_string())
+}
+
+/// 매크로 하나를 셸 스크립트로 내보내 설정 파일 옆에 저장한다 (keym-<id>-xdotool.sh). 저장한 경로를 돌려준다
+#[tauri::command]
+fn export_macro(config: MacroConfig, id: String, format: String) -> Result<String, String> {
+    let tool = match format.as_str() {
+        "xdotool" => convert::shell::Tool::Xdotool,
+        "ydotool" => convert::shell::Tool::Ydotool,
+        _ => return Err(format!("지원하지 않는 형식: {}", format)),
+    };
+    let macro_item = config
+        .get_macro_by_id(&id)
+        .ok_or_else(|| format!("매크로를 찾을 수 없습니다: {}", id))?;
+    let content = convert::shell::export_macro(&config, macro_item, tool).map_err(|e| e.to_string())?;
+
+    let name: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let path = config_location().path.with_file_name(format!("keym-{}-{}.sh", name, format));
+    std::fs::write(&path, content)
+        .map_err(|e| format!("내보내기 실패 ({}): {}", path.display(), e))?;
+    Ok(path.display().to_string())
+}
+
+#[derive(Debug, Serialize)]
+struct AhkImportResult {
+    config: MacroConfig,
+    /// 건너뛴 줄 (줄 번호, 내용, 이유)
+    unsupported: Vec<convert::ahk::UnsupportedLine>,
+}
+
+/// AutoHotkey 스크립트의 핫키를 매크로로 가져와 뒤에 붙인다. 토글 키는 바꾸지 않는다
+#[tauri::command]
+fn import_ahk(mut config: MacroConfig, path: String) -> Result<AhkImportResult, String> {
+    let source = std::fs::read_to_string(&path)
+        .map_err(|e| format!("파일 읽기 실패 ({}): {}", path, e))?;
+    let imported = convert::ahk::import(&source);
+    config.macros.extend(imported.macros);
+    Ok(AhkImportResult { config, unsupported: imported.unsupported })
+}
+
+/// 고른 매크로(와 call로 부르는 매크로)를 JSON 번들로 저장한다. 저장한 매크로 수를 돌려준다
+#[tauri::command]
+fn export_macro_bundle(config: MacroConfig, ids: Vec<String>, path: String) -> Result<usize, String> {
+    let bundle = MacroBundle::export(&config, &ids);
+    if bundle.macros.is_empty() {
+        return Err("내보낼 매크로가 없습니다".to_string());
+    }
+    std::fs::write(&path, bundle.to_json())
+        .map_err(|e| format!("내보내기 실패 ({}): {}", path, e))?;
+    Ok(bundle.macros.len())
+}
+
+#[derive(Debug, Serialize)]
+struct BundlePreview {
+    bundle: MacroBundle,
+    /// 처리 방법을 골라야 하는 트리거 충돌
+    conflicts: Vec<BundleConflict>,
+}
+
+/// 번들을 읽고 현재 설정과 겹치는 트리거를 찾는다 (아직 추가하지 않는다)
+#[tauri::command]
+fn read_macro_bundle(config: MacroConfig, path: String) -> Result<BundlePreview, String> {
+    let json = std::fs::read_to_string(&path)
+        .map_err(|e| format!("파일 읽기 실패 ({}): {}", path, e))?;
+    let bundle = MacroBundle::parse(&json).map_err(|e| e.to_string())?;
+    let conflicts = bundle.conflicts(&config);
+    Ok(BundlePreview { bundle, conflicts })
+}
+
+/// 충돌마다 고른 처리 방법(건너뛰기, 트리거 바꾸기, 덮어쓰기)으로 번들을 추가한다
+#[tauri::command]
+fn import_macro_bundle(
+    mut config: MacroConfig,
+    bundle: MacroBundle,
+    resolutions: BTreeMap<String, Resolution>,
+) -> Result<MacroConfig, String> {
+    config.import_bundle(bundle, &resolutions).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+/// 액션 목록을 문법으로 내보내기 (예: "tap a 30ms; wait 100ms")
+#[tauri::command]
+fn export_macro_script(mut config: MacroConfig, id: String) -> Result<String, String> {
+    Ok(dsl::print(&find_macro(&mut config, &id)?.actions))
+}
+
+/// 문법으로 쓴 액션으로 바꾸기
+#[tauri::command]
+fn import_macro_script(mut config: MacroConfig, id: String, script: String) -> Result<MacroConfig, String> {
+    let actions = dsl::parse(&script).map_err(|e| format!("문법 오류: {}", e))?;
+    find_macro(&mut config, &id)?.actions = actions;
+    Ok(config)
+}
+
+/// 겹치는 트리거, 토글 키와 같은 트리거, 가로채이는 조합키, 자기 트리거를 누르는 매크로
+#[tauri::command]
+fn analyze_conflicts(config: MacroConfig) -> Vec<TriggerConflict> {
+    config.trigger_conflicts()
+}
+
+/// 키 바꾸기 표 교체. 알 수 없는 키나 트리거를 가리는 항목이 있으면 거부한다
+#[tauri::command]
+fn update_remaps(mut config: MacroConfig, remap: BTreeMap<String, String>) -> Result<MacroConfig, String> {
+    config.remap = remap;
+    let errors: Vec<String> = shared::remap::check(&config).iter().map(|e| e.to_string()).collect();
+    if !errors.is_empty() {
+        return Err(errors.join(", "));
+    }
+    Ok(config)
+}
+
+/// 레이어 목록 교체. 이름이나 키가 겹치거나 없는 레이어를 쓰는 매크로가 있으면 거부한다
+#[tauri::command]
+fn update_layers(mut config: MacroConfig, layers: Vec<Layer>) -> Result<MacroConfig, String> {
+    config.layers = layers;
+    let errors: Vec<String> = shared::layer::check(&config).iter().map(|e| e.to_string()).collect();
+    if !errors.is_empty() {
+        return Err(errors.join(", "));
+    }
+    Ok(config)
+}
+
+/// call, repeat, 매개변수를 펼친 결과 (Runner가 실행할 것과 같다)
+#[tauri::command]
+fn preview_macro(config: MacroConfig, id: String) -> Result<Macro, String> {
+    let macro_item = config.get_macro_by_id(&id)
+        .ok_or_else(|| format!("매크로를 찾을 수 없습니다: {}", id))?;
+    config.expand(macro_item).map_err(|e| e.to_string())
+}
+
+/// Runner 채널에 연결하고 토큰을 읽는다
+fn connect_runner() -> Result<(std::net::TcpStream, String), String> {
+    let stream = ipc::connect()
+        .map_err(|_| "Runner가 실행 중이 아닙니다".to_string())?;
+    let token = ipc::read_token(&ipc::token_path())
+        .map_err(|e| format!("Runner 토큰을 읽을 수 없습니다: {}", e))?;
+    Ok((stream, token))
+}
+
+/// 저장하지 않은 설정으로 펼쳐서 보낸다 (Runner는 저장된 설정만 알고 있다)
+#[tauri::command]
+fn test_play_macro(
+    window: tauri::Window,
+    config: MacroConfig,
+    id: String,
+    countdown_ms: u64,
+) -> Result<(), String> {
+    let macro_item = config.get_macro_by_id(&id)
+        .ok_or_else(|| format!("매크로를 찾을 수 없습니다: {}", id))?;
+    let expanded = config.expand(macro_item).map_err(|e| e.to_string())?;
+    
+    let (mut stream, token) = connect_runner()?;
+    let request = IpcRequest::Play { macro_item: Box::new(expanded), countdown_ms };
+    ipc::send_request(&mut stream, token, request)
+        .map_err(|e| format!("전송 실패: {}", e))?;
+    
+    // Runner가 연결을 닫을 때까지 진행 상황을 UI로 전달
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        while let Ok(Some(event)) = ipc::read_message::<_, IpcEvent>(&mut reader) {
+            let _ = window.emit("test-play", event);
+        }
+    });
+    
+    Ok(())
+}
+
+#[tauri::command]
+fn cancel_test_play() -> Result<(), String> {
+    let (mut stream, token) = connect_runner()?;
+    ipc::send_request(&mut stream, token, IpcRequest::Cancel)
+        .map_err(|e| format!("전송 실패: {}", e))
+}
+
 fn main() {
     // 중복 실행 방지
     let _mutex = match AppMutex::new("editor") {
@@ -177,15 +427,33 @@ fn main() {
     tauri::Builder::default()
         .invoke_handler(tauri::generate_handler![
             get_available_keys,
+            get_config_location,
             load_config,
             save_config,
+            list_backups,
+            restore_backup,
             add_macro,
             update_macro,
             delete_macro,
             add_action,
+            add_call_action,
             update_action,
             delete_action,
             move_action,
+            export_config,
+            export_macro,
+            import_ahk,
+            export_macro_bundle,
+            read_macro_bundle,
+            import_macro_bundle,
+            export_macro_script,
+            import_macro_script,
+            preview_macro,
+            analyze_conflicts,
+            update_remaps,
+            update_layers,
+            test_play_macro,
+            cancel_test_play,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use crate::log::Level;
+
+pub const USAGE: &str = "\
+사용법: keym [옵션]
+
+옵션:
+  --config <경로>                      설정 파일 (기본: KEYM_CONFIG → 실행 파일 폴더 → 사용자 설정 폴더)
+  --log-level <debug|info|warn|error>  콘솔 출력 수준 (기본: info)
+  --log-file <경로>                    JSON-lines 이벤트 로그 파일 (모든 수준 기록)
+  --measure <트리거>                   키 입력 없이 매크로를 실행해 타이밍 통계만 출력
+  --runs <횟수>                        --measure 반복 횟수 (기본: 5)
+  -h, --help                           도움말";
+
+/// 명령행 옵션
+#[derive(Debug)]
+pub struct Args {
+    pub config: Option<PathBuf>,
+    pub log_level: Level,
+    pub log_file: Option<PathBuf>,
+    pub measure: Option<String>,
+    pub runs: usize,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut parsed = Args {
+            config: None,
+            log_level: Level::Info,
+            log_file: None,
+            measure: None,
+            runs: 5,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => {
+                    let value = args.next().ok_or("--config 값이 없습니다")?;
+                    parsed.config = Some(PathBuf::from(value));
+                }
+                "--log-level" => {
+                    let value = args.next().ok_or("--log-level 값이 없습니다")?;
+                    parsed.log_level = Level::parse(&value)
+                        .ok_or_else(|| format!("알 수 없는 로그 수준: {}", value))?;
+                }
+                "--log-file" => {
+                    let value = args.next().ok_or("--log-file 값이 없습니다")?;
+                    parsed.log_file = Some(PathBuf::from(value));
+                }
+                "--measure" => {
+                    let value = args.next().ok_or("--measure 값이 없습니다")?;
+                    parsed.measure = Some(value);
+                }
+                "--runs" => {
+                    let value = args.next().ok_or("--runs 값이 없습니다")?;
+                    parsed.runs = value
+                        .parse()
+                        .map_err(|_| format!("잘못된 반복 횟수: {}", value))?;
+                }
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                other => return Err(format!("알 수 없는 옵션: {}\n\n{}", other, USAGE)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse_from(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn defaults() {
+        let args = parse(&[]).unwrap();
+        assert_eq!(args.log_level, Level::Info);
+        assert_eq!(args.runs, 5);
+        assert!(args.config.is_none() && args.log_file.is_none() && args.measure.is_none());
+    }
+
+    #[test]
+    fn log_level_is_parsed() {
+        assert_eq!(parse(&["--log-level", "debug"]).unwrap().log_level, Level::Debug);
+        assert_eq!(parse(&["--log-level", "Error"]).unwrap().log_level, Level::Error);
+        assert_eq!(parse(&["--log-level", "loud"]).unwrap_err(), "알 수 없는 로그 수준: loud");
+        assert_eq!(parse(&["--log-level"]).unwrap_err(), "--log-level 값이 없습니다");
+    }
+
+    #[test]
+    fn other_options() {
+        let args = parse(&["--log-file", "keym.jsonl", "--measure", "f1", "--runs", "3"]).unwrap();
+        assert_eq!(args.log_file, Some(PathBuf::from("keym.jsonl")));
+        assert_eq!(args.measure.as_deref(), Some("f1"));
+        assert_eq!(args.runs, 3);
+        assert_eq!(parse(&["--runs", "many"]).unwrap_err(), "잘못된 반복 횟수: many");
+        assert!(parse(&["--verbose"]).unwrap_err().starts_with("알 수 없는 옵션: --verbose"));
+    }
+}
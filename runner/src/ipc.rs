@@ -0,0 +1,106 @@
+use shared::executor::{sleep_cancellable, SystemClock};
+use shared::ipc::{self, IpcEvent, IpcRequest, IPC_ADDR};
+use shared::MacroConfig;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::log::{self, Event, Source};
+use crate::{execute_macro, AppState};
+
+/// Editor의 테스트 재생 요청을 받는 서버 시작.
+/// 실행할 때마다 새 토큰을 파일에 쓰고, 토큰이 맞는 요청만 받는다
+pub fn spawn_server(state: Arc<AppState>) {
+    let token = Arc::new(ipc::new_token());
+    if let Err(e) = ipc::write_token(&ipc::token_path(), &token) {
+        log::warn(Event::ChannelFailed { addr: IPC_ADDR, error: format!("토큰 파일: {}", e) });
+        return;
+    }
+    let listener = match TcpListener::bind(IPC_ADDR) {
+        Ok(l) => l,
+        Err(e) => {
+            log::warn(Event::ChannelFailed { addr: IPC_ADDR, error: e.to_string() });
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let state = state.clone();
+            let token = token.clone();
+            std::thread::spawn(move || handle_client(stream, &state, &token));
+        }
+    });
+}
+
+fn handle_client(stream: TcpStream, state: &AppState, token: &str) {
+    let Ok(read_half) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read_half);
+    let mut writer = stream;
+
+    match ipc::read_request(&mut reader, token) {
+        Ok(Some(IpcRequest::Play { macro_item, countdown_ms })) => {
+            test_play(state, &macro_item, countdown_ms, &mut writer);
+        }
+        Ok(Some(IpcRequest::Cancel)) => {
+            state.cancel_requested.store(true, Ordering::SeqCst);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            let _ = ipc::write_message(&mut writer, &IpcEvent::Error {
+                message: format!("잘못된 요청: {}", e),
+            });
+        }
+    }
+}
+
+fn test_play(state: &AppState, macro_item: &shared::Macro, countdown_ms: u64, writer: &mut TcpStream) {
+    // Editor가 저장하지 않은 설정으로 펼쳐 보낸다. 남은 call은 Runner 설정으로 풀지 않고 거부한다
+    let macro_item = match MacroConfig::default().expand(macro_item) {
+        Ok(m) => m,
+        Err(e) => {
+            log::error(Event::MacroExpandFailed { trigger: &macro_item.trigger, error: e.to_string() });
+            let _ = ipc::write_message(writer, &IpcEvent::Error { message: e.to_string() });
+            return;
+        }
+    };
+
+    // 확인과 점유를 한 번에 해야 트리거 실행과 겹치지 않는다
+    if let Err(e) = state.runs.lock().unwrap().start(None, false) {
+        let _ = ipc::write_message(writer, &IpcEvent::Error { message: e.to_string() });
+        return;
+    }
+
+    let cancel = &state.cancel_requested;
+    cancel.store(false, Ordering::SeqCst);
+    log::info(Event::TestPlayRequested { trigger: &macro_item.trigger, countdown_ms });
+
+    let clock = SystemClock::new();
+    let mut remaining = countdown_ms;
+    let mut counted = true;
+    while remaining > 0 {
+        let _ = ipc::write_message(writer, &IpcEvent::Countdown { remaining_ms: remaining });
+        let step = remaining.min(1000);
+        if !sleep_cancellable(&clock, Duration::from_millis(step), cancel) {
+            counted = false;
+            break;
+        }
+        remaining -= step;
+    }
+
+    let completed = counted && {
+        let total = macro_item.actions.len();
+        let _ = ipc::write_message(writer, &IpcEvent::Started { total });
+        execute_macro(&macro_item, Source::TestPlay, cancel, |done, total| {
+            let _ = ipc::write_message(writer, &IpcEvent::Progress { done, total });
+        })
+    };
+
+    let event = if completed { IpcEvent::Finished } else { IpcEvent::Cancelled };
+    let _ = ipc::write_message(writer, &event);
+    state.runs.lock().unwrap().finish(None);
+}
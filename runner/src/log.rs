@@ -0,0 +1,279 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use shared::config::ConfigSource;
+use shared::TimingStats;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// 로그 수준
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "debug" => Some(Level::Debug),
+            "info" => Some(Level::Info),
+            "warn" => Some(Level::Warn),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+}
+
+/// 매크로가 어디서 실행되었는지
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Trigger,
+    TestPlay,
+}
+
+/// 기록되는 이벤트. 이름과 필드가 그대로 JSON 키가 된다
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    RunnerStarted { version: &'a str },
+    AlreadyRunning,
+    ConfigLoaded { path: &'a Path, source: ConfigSource, macros: usize, toggle_key: &'a str },
+    ConfigLoadFailed { path: &'a Path, source: ConfigSource, error: String },
+    ConfigIncluded { path: &'a Path },
+    MacroConflict { trigger: &'a str, kept: &'a Path, dropped: &'a Path },
+    MacroRegistered { id: &'a str, name: &'a str, trigger: &'a str, actions: usize, enabled: bool },
+    HookInstalled,
+    ChannelFailed { addr: &'a str, error: String },
+    ToggleChanged { enabled: bool },
+    LayerChanged { layer: Option<&'a str> },
+    TriggerDetected { key: &'a str },
+    TestPlayRequested { trigger: &'a str, countdown_ms: u64 },
+    MacroStarted { trigger: &'a str, source: Source, actions: usize },
+    KeyInjected { key: &'a str, keyup: bool, drift_us: i64 },
+    UnknownKey { key: &'a str },
+    MacroFinished { trigger: &'a str, elapsed_ms: f64 },
+    MacroCancelled { trigger: &'a str, done: usize, total: usize },
+    MacroTiming { trigger: &'a str, timing: &'a TimingStats },
+    MacroNotFound { trigger: &'a str },
+    MacroInvalid { error: String },
+    TriggerConflict { error: String },
+    RemapInvalid { error: String },
+    LayerInvalid { error: String },
+    MacroExpandFailed { trigger: &'a str, error: String },
+    ChainRejected { trigger: &'a str, error: String },
+    MeasureRun { trigger: &'a str, run: usize, timing: &'a TimingStats },
+}
+
+/// 로거 스레드가 받는 메시지
+enum Message {
+    Record(Record),
+    /// 그때까지 받은 기록을 모두 쓰고 알린다
+    Flush(Sender<()>),
+}
+
+/// 훅 스레드에서 만든 기록. 출력과 파일 쓰기는 로거 스레드가 한다
+struct Record {
+    level: Level,
+    name: String,
+    fields: Map<String, Value>,
+    elapsed: f64,
+    ts_ms: u64,
+    console: bool,
+}
+
+struct Logger {
+    console_level: Level,
+    started: Instant,
+    has_file: bool,
+    sender: Sender<Message>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+impl Logger {
+    fn spawn(console_level: Level, file: Option<File>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let has_file = file.is_some();
+        thread::spawn(move || write_records(receiver, file));
+        Self { console_level, started: Instant::now(), has_file, sender }
+    }
+
+    fn log(&self, level: Level, event: Event) {
+        let console = level >= self.console_level;
+        if !console && !self.has_file {
+            return;
+        }
+        let Ok(Value::Object(mut fields)) = serde_json::to_value(&event) else {
+            return;
+        };
+        let name = match fields.remove("event") {
+            Some(Value::String(name)) => name,
+            _ => return,
+        };
+        let ts_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let _ = self.sender.send(Message::Record(Record { level, name, fields, elapsed, ts_ms, console }));
+    }
+
+    fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        if self.sender.send(Message::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+}
+
+fn write_records(receiver: Receiver<Message>, mut file: Option<File>) {
+    for message in receiver {
+        match message {
+            Message::Record(record) => {
+                if record.console {
+                    let line = format_console(record.elapsed, record.level, &record.name, &record.fields);
+                    if record.level >= Level::Warn {
+                        eprintln!("{}", line);
+                    } else {
+                        println!("{}", line);
+                    }
+                }
+                if let Some(file) = &mut file {
+                    let _ = writeln!(file, "{}", json_record(record));
+                }
+            }
+            Message::Flush(done) => {
+                if let Some(file) = &mut file {
+                    let _ = file.flush();
+                }
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+/// 로거 초기화. JSON 로그 파일에는 수준과 관계없이 모든 이벤트가 남는다.
+/// 출력과 파일 쓰기는 로거 스레드에서 하므로 키보드 훅을 막지 않는다
+pub fn init(console_level: Level, json_path: Option<&Path>) -> io::Result<()> {
+    let file = match json_path {
+        Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+        None => None,
+    };
+
+    let _ = LOGGER.set(Logger::spawn(console_level, file));
+    Ok(())
+}
+
+/// 보낸 기록이 모두 쓰일 때까지 기다린다. 프로세스를 끝내기 전에 부른다
+pub fn flush() {
+    if let Some(logger) = LOGGER.get() {
+        logger.flush();
+    }
+}
+
+pub fn debug(event: Event) {
+    log(Level::Debug, event);
+}
+
+pub fn info(event: Event) {
+    log(Level::Info, event);
+}
+
+pub fn warn(event: Event) {
+    log(Level::Warn, event);
+}
+
+pub fn error(event: Event) {
+    log(Level::Error, event);
+}
+
+pub fn log(level: Level, event: Event) {
+    if let Some(logger) = LOGGER.get() {
+        logger.log(level, event);
+    }
+}
+
+fn json_record(record: Record) -> Value {
+    let mut json = Map::new();
+    json.insert("ts_ms".to_string(), record.ts_ms.into());
+    json.insert("level".to_string(), record.level.label().into());
+    json.insert("event".to_string(), record.name.into());
+    json.extend(record.fields);
+    Value::Object(json)
+}
+
+fn format_console(elapsed: f64, level: Level, name: &str, fields: &Map<String, Value>) -> String {
+    let mut line = format!("[{:>9.3}s] {:<5} {}", elapsed, level.label().to_uppercase(), name);
+    for (key, value) in fields {
+        match value {
+            Value::String(s) => line.push_str(&format!(" {}={:?}", key, s)),
+            other => line.push_str(&format!(" {}={}", key, other)),
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_parse_case_insensitively() {
+        assert_eq!(Level::parse("DEBUG"), Some(Level::Debug));
+        assert_eq!(Level::parse("warn"), Some(Level::Warn));
+        assert_eq!(Level::parse("verbose"), None);
+        assert!(Level::Debug < Level::Info && Level::Warn < Level::Error);
+    }
+
+    #[test]
+    fn console_line_lists_fields() {
+        let Value::Object(mut fields) = serde_json::to_value(Event::UnknownKey { key: "f13" }).unwrap() else {
+            unreachable!();
+        };
+        fields.remove("event");
+        assert_eq!(
+            format_console(1.5, Level::Warn, "unknown_key", &fields),
+            "[    1.500s] WARN  unknown_key key=\"f13\""
+        );
+    }
+
+    #[test]
+    fn file_gets_every_level_after_flush() {
+        let path = std::env::temp_dir().join(format!("keym-log-test-{}.jsonl", std::process::id()));
+        let file = OpenOptions::new().create(true).write(true).truncate(true).open(&path).unwrap();
+        let logger = Logger::spawn(Level::Error, Some(file));
+        logger.log(Level::Debug, Event::KeyInjected { key: "a", keyup: false, drift_us: 12 });
+        logger.log(Level::Info, Event::ToggleChanged { enabled: false });
+        logger.flush();
+
+        let lines: Vec<Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "key_injected");
+        assert_eq!(lines[0]["level"], "debug");
+        assert_eq!(lines[0]["drift_us"], 12);
+        assert_eq!(lines[1]["event"], "toggle_changed");
+        assert_eq!(lines[1]["enabled"], false);
+    }
+}
@@ -1,53 +1,199 @@
-use shared::{AppMutex, MacroConfig, InputSystem};
+mod cli;
+mod ipc;
+mod log;
+
+use cli::Args;
+use log::{Event, Source};
+use shared::executor::{ExecEvent, NullBackend, SendInputBackend, SystemClock};
+use shared::config::Passthrough;
+use shared::gesture::{Decision, GestureRecognizer};
+use shared::input::{InputSystem, INJECTED_MARK, REMAP_MARK};
+use shared::layer::{self, LayerEvent, LayerState};
+use shared::remap::{self, Remapper};
+use shared::running::{RunningMacros, StartError};
+use shared::sequence::SequenceMatcher;
+use shared::trigger::{self, Chord};
+use shared::{AppMutex, Executor, Macro, MacroConfig};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use windows::Win32::Foundation::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
 static mut HOOK: HHOOK = HHOOK(0);
 static mut APP_DATA: Option<Arc<AppState>> = None;
+/// 눌려 있는 수정 키 (trigger::CTRL | ALT | SHIFT)
+static MODIFIERS: AtomicU8 = AtomicU8::new(0);
 
 struct AppState {
     config: MacroConfig,
-    enabled: Mutex<bool>,
-    running_macro: Arc<Mutex<bool>>,
+    /// 실행 중인 매크로 (체인으로 함께 도는 매크로 포함)
+    runs: Mutex<RunningMacros>,
+    cancel_requested: AtomicBool,
+    /// 토글 키로 켜고 끈 상태와 활성 레이어
+    layers: Mutex<LayerState>,
+    /// 기본 레이어, 그 뒤로 config.layers 순서
+    keymaps: Vec<Keymap>,
+    /// 제스처 시간 기준
+    started: Instant,
+}
+
+impl AppState {
+    /// 활성 레이어의 트리거와 키 바꾸기
+    fn active_keymap(&self) -> &Keymap {
+        let layers = self.layers.lock().unwrap();
+        let index = layers
+            .active()
+            .and_then(|name| self.config.layers.iter().position(|l| l.name == name))
+            .map_or(0, |i| i + 1);
+        &self.keymaps[index]
+    }
+}
+
+/// 한 레이어에서 쓰는 트리거와 키 바꾸기. 매크로 인덱스는 config.macros 그대로다
+struct Keymap {
+    /// 뗄 때, 길게, 두 번 누르는 트리거
+    gestures: Mutex<GestureRecognizer>,
+    /// 차례로 누르는 트리거 ("f12 g 1")
+    sequences: Mutex<SequenceMatcher>,
+    /// 키 바꾸기. 훅 안에서 바로 보낸다
+    remaps: Mutex<Remapper>,
+}
+
+impl Keymap {
+    fn new(config: &MacroConfig, layer: Option<&str>) -> Self {
+        let view = layer::view(config, layer);
+        Self {
+            gestures: Mutex::new(GestureRecognizer::new(&view)),
+            sequences: Mutex::new(SequenceMatcher::new(&view)),
+            remaps: Mutex::new(Remapper::new(&view)),
+        }
+    }
 }
 
+/// 제스처 시계를 확인하는 간격
+const GESTURE_POLL: Duration = Duration::from_millis(5);
+
 fn main() {
-    println!("=== KeyM Runner v0.1 ===");
-    
-    // 중복 실행 방지
-    let _mutex = match AppMutex::new("runner") {
-        Some(m) => m,
-        None => {
-            eprintln!("Runner가 이미 실행 중입니다!");
-            std::thread::sleep(Duration::from_secs(3));
+    let args = match Args::parse() {
+        Ok(a) => a,
+        Err(message) => {
+            eprintln!("{}", message);
             return;
         }
     };
     
+    if let Err(e) = log::init(args.log_level, args.log_file.as_deref()) {
+        eprintln!("로그 파일 열기 실패: {}", e);
+        return;
+    }
+    log::info(Event::RunnerStarted { version: env!("CARGO_PKG_VERSION") });
+    
     // 설정 로드
-    let config = match MacroConfig::load("config.toml") {
-        Ok(c) => c,
+    let location = shared::config::locate(args.config.as_deref());
+    let resolved = match MacroConfig::load_resolved(&location.path) {
+        Ok(r) => r,
         Err(e) => {
-            eprintln!("설정 파일 로드 실패: {}", e);
-            eprintln!("Editor로 매크로를 먼저 생성하세요.");
+            log::error(Event::ConfigLoadFailed {
+                path: &location.path,
+                source: location.source,
+                error: e.to_string(),
+            });
+            log::flush();
             std::thread::sleep(Duration::from_secs(3));
             return;
         }
     };
     
-    println!("매크로 {} 개 로드됨", config.macros.len());
-    println!("토글 키: {} (비어있으면 항상 활성)", config.toggle_key);
-    println!("\n매크로 목록:");
+    let config = resolved.config;
+    for file in &resolved.files[..resolved.files.len() - 1] {
+        log::info(Event::ConfigIncluded { path: file });
+    }
+    for conflict in &resolved.conflicts {
+        log::warn(Event::MacroConflict {
+            trigger: &conflict.trigger,
+            kept: &conflict.kept,
+            dropped: &conflict.dropped,
+        });
+    }
+    log::info(Event::ConfigLoaded {
+        path: &location.path,
+        source: location.source,
+        macros: config.macros.len(),
+        toggle_key: &config.toggle_key,
+    });
     for m in &config.macros {
-        println!("  [{}] - {}개 액션", m.trigger, m.actions.len());
+        log::info(Event::MacroRegistered {
+            id: &m.id,
+            name: m.display_name(),
+            trigger: &m.trigger,
+            actions: m.actions.len(),
+            enabled: m.enabled,
+        });
+    }
+    for error in config.check_macros() {
+        log::warn(Event::MacroInvalid { error: error.to_string() });
+    }
+    for conflict in config.trigger_conflicts() {
+        log::warn(Event::TriggerConflict { error: conflict.to_string() });
+    }
+    for error in remap::check(&config) {
+        log::warn(Event::RemapInvalid { error: error.to_string() });
+    }
+    for error in layer::check(&config) {
+        log::warn(Event::LayerInvalid { error: error.to_string() });
+    }
+    
+    if let Some(trigger) = &args.measure {
+        measure(&config, trigger, args.runs);
+        log::flush();
+        return;
     }
     
+    // 중복 실행 방지
+    let _mutex = match AppMutex::new("runner") {
+        Some(m) => m,
+        None => {
+            log::error(Event::AlreadyRunning);
+            log::flush();
+            std::thread::sleep(Duration::from_secs(3));
+            return;
+        }
+    };
+    
+    let keymaps = std::iter::once(None)
+        .chain(config.layers.iter().map(|l| Some(l.name.as_str())))
+        .map(|layer| Keymap::new(&config, layer))
+        .collect();
     let app_state = Arc::new(AppState {
+        layers: Mutex::new(LayerState::new(&config)),
+        keymaps,
+        runs: Mutex::new(RunningMacros::new(config.chain_triggers)),
         config,
-        enabled: Mutex::new(true),
-        running_macro: Arc::new(Mutex::new(false)),
+        cancel_requested: AtomicBool::new(false),
+        started: Instant::now(),
+    });
+    
+    // Editor 테스트 재생 채널
+    ipc::spawn_server(app_state.clone());
+    
+    // 길게 누름, 기다린 tap, 끊긴 순서는 키 이벤트 없이 시간이 지나 결정된다.
+    // 레이어가 바뀌어도 이전 레이어에서 시작한 입력은 마저 처리한다
+    let ticker_state = app_state.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(GESTURE_POLL);
+        if !ticker_state.layers.lock().unwrap().is_enabled() {
+            continue;
+        }
+        let now = ticker_state.started.elapsed().as_millis() as u64;
+        for keymap in &ticker_state.keymaps {
+            let fired = keymap.gestures.lock().unwrap().poll(now);
+            for index in fired {
+                start_macro(&ticker_state, index, false);
+            }
+            let replay = keymap.sequences.lock().unwrap().poll(now);
+            replay_chords(&replay);
+        }
     });
     
     unsafe {
@@ -61,8 +207,7 @@ fn main() {
             0,
         ).expect("Failed to install hook");
         
-        println!("\n! 키보드 후킹 활성화");
-        println!("! 매크로 대기 중... (Ctrl+C로 종료)\n");
+        log::info(Event::HookInstalled);
         
         // 메시지 루프
         let mut msg = MSG::default();
@@ -74,6 +219,7 @@ fn main() {
         // 정리
         let _ = UnhookWindowsHookEx(HOOK);
     }
+    log::flush();
 }
 
 unsafe extern "system" fn keyboard_proc(
@@ -88,9 +234,11 @@ unsafe extern "system" fn keyboard_proc(
     let kb = *(lparam.0 as *const KBDLLHOOKSTRUCT);
     let is_keydown = wparam.0 == WM_KEYDOWN as usize || wparam.0 == WM_SYSKEYDOWN as usize;
     
-    if !is_keydown {
-        return CallNextHookEx(None, code, wparam, lparam);
-    }
+    // 스캔코드를 키 이름으로 변환
+    let scancode = kb.scanCode as u16;
+    let is_extended = (kb.flags.0 & LLKHF_EXTENDED.0) != 0;
+    
+    let key_name = scancode_to_key_name(scancode, is_extended);
     
     // APP_DATA 가져오기
     let app_state = unsafe {
@@ -101,54 +249,170 @@ unsafe extern "system" fn keyboard_proc(
         }
     };
     
-    // 스캔코드를 키 이름으로 변환
-    let scancode = kb.scanCode as u16;
-    let is_extended = (kb.flags.0 & LLKHF_EXTENDED.0) != 0;
+    // 키 바꾸기. 바꾼 키는 REMAP_MARK로 다시 들어와 실제로 누른 키처럼 처리된다.
+    // 뗄 때는 꺼져 있거나 레이어가 바뀌었어도 누를 때 보낸 키를 뗀다
+    if kb.dwExtraInfo != REMAP_MARK && kb.dwExtraInfo != INJECTED_MARK {
+        let target = if !is_keydown {
+            app_state.keymaps.iter().find_map(|k| k.remaps.lock().unwrap().key_up(&key_name))
+        } else if app_state.layers.lock().unwrap().is_enabled() {
+            let mut remaps = app_state.active_keymap().remaps.lock().unwrap();
+            if remaps.is_empty() { None } else { remaps.key_down(&key_name) }
+        } else {
+            None
+        };
+        if let Some(target) = target {
+            InputSystem::send_remapped(&target, !is_keydown);
+            return LRESULT(1); // 원래 키 소비
+        }
+    }
     
-    let key_name = scancode_to_key_name(scancode, is_extended);
+    // 수정 키 상태는 누를 때와 뗄 때 모두 갱신
+    if let Some(bit) = trigger::modifier_bit(&key_name) {
+        if is_keydown {
+            MODIFIERS.fetch_or(bit, Ordering::SeqCst);
+        } else {
+            MODIFIERS.fetch_and(!bit, Ordering::SeqCst);
+        }
+    }
+    
+    // 매크로가 주입한 키는 트리거로 보지 않는다 (수정 키 상태는 위에서 반영)
+    let injected = kb.dwExtraInfo == INJECTED_MARK;
+    if injected && !app_state.config.chain_triggers {
+        return CallNextHookEx(None, code, wparam, lparam);
+    }
+    
+    let chord = Chord::new(MODIFIERS.load(Ordering::SeqCst), key_name.as_str());
     
-    // 토글 키 확인
-    if !app_state.config.toggle_key.is_empty() && key_name == app_state.config.toggle_key {
-        let mut enabled = app_state.enabled.lock().unwrap();
-        *enabled = !*enabled;
-        println!("매크로 {}", if *enabled { "활성화" } else { "비활성화" });
-        return LRESULT(1); // 키 소비
+    // 토글 키와 레이어 키. 누를 때와 뗄 때 모두 소비한다
+    if !injected {
+        let event = {
+            let mut layers = app_state.layers.lock().unwrap();
+            if is_keydown { layers.key_down(&chord) } else { layers.key_up(&chord.key) }
+        };
+        match event {
+            LayerEvent::Pass => {}
+            LayerEvent::Consume => return LRESULT(1),
+            LayerEvent::Enabled(enabled) => {
+                log::info(Event::ToggleChanged { enabled });
+                return LRESULT(1);
+            }
+            LayerEvent::Active(layer) => {
+                log::info(Event::LayerChanged { layer: layer.as_deref() });
+                return LRESULT(1);
+            }
+        }
     }
     
     // 활성화 상태 확인
-    let enabled = *app_state.enabled.lock().unwrap();
-    if !enabled {
+    if !app_state.layers.lock().unwrap().is_enabled() {
         return CallNextHookEx(None, code, wparam, lparam);
     }
     
-    // 이미 매크로 실행 중이면 무시
-    let running = *app_state.running_macro.lock().unwrap();
-    if running {
-        return CallNextHookEx(None, code, wparam, lparam);
+    let now = app_state.started.elapsed().as_millis() as u64;
+    let keymap = app_state.active_keymap();
+    
+    // 순서 트리거. 주입한 키는 되돌려 보낸 키일 수 있으므로 보지 않는다
+    if !injected {
+        if is_keydown {
+            let mut sequences = keymap.sequences.lock().unwrap();
+            if !sequences.is_empty() {
+                let decision = sequences.key_down(&chord, now);
+                drop(sequences);
+                replay_chords(&decision.replay);
+                if let Some(index) = decision.fire {
+                    start_macro(app_state, index, false);
+                }
+                if decision.consume {
+                    return LRESULT(1); // 순서 중인 키 소비
+                }
+            }
+        } else if app_state.keymaps.iter().any(|k| k.sequences.lock().unwrap().key_up(&chord.key)) {
+            return LRESULT(1);
+        }
     }
     
-    // 매크로 찾기
-    for macro_item in &app_state.config.macros {
-        if macro_item.trigger == key_name {
-            println!("트리거 감지: [{}]", key_name);
-            
-            // 매크로 실행 (별도 스레드)
-            let macro_clone = macro_item.clone();
-            let running_flag = app_state.running_macro.clone();
-            
-            std::thread::spawn(move || {
-                *running_flag.lock().unwrap() = true;
-                execute_macro(&macro_clone);
-                *running_flag.lock().unwrap() = false;
-            });
-            
-            return LRESULT(1); // 트리거 키 소비
+    // 제스처 판단. 트리거에 묶인 키는 누를 때와 뗄 때 모두 소비한다.
+    // 뗄 때는 누를 때의 레이어에서 처리한다 (눌려 있는 키는 한 레이어에만 있다)
+    // 매크로 실행 중에는 (체인 트리거가 아니면) 실행하지 않으므로 키를 삼키지 않고 넘긴다
+    let decision = if is_keydown {
+        let busy = !injected && app_state.runs.lock().unwrap().is_running();
+        let mut gestures = keymap.gestures.lock().unwrap();
+        if busy { gestures.key_down_while_running(&chord) } else { gestures.key_down(&chord, now) }
+    } else {
+        let mut decision = Decision::default();
+        for keymap in &app_state.keymaps {
+            let up = keymap.gestures.lock().unwrap().key_up(&chord.key, now);
+            decision.fire.extend(up.fire);
+            decision.consume |= up.consume;
         }
+        decision
+    };
+    for index in decision.fire {
+        start_macro(app_state, index, injected);
     }
     
+    if decision.consume {
+        return LRESULT(1); // 트리거 키 소비
+    }
     CallNextHookEx(None, code, wparam, lparam)
 }
 
+/// config.macros[index]를 별도 스레드에서 펼쳐 실행 (펼치기가 훅을 막지 않도록).
+/// 이미 매크로 실행 중이면 무시한다. 매크로가 누른 키(체인 트리거)로 시작하면 실행 중인 매크로와 함께 돈다
+fn start_macro(app_state: &Arc<AppState>, index: usize, injected: bool) {
+    let trigger = app_state.config.macros[index].trigger.as_str();
+    match app_state.runs.lock().unwrap().start(Some(index), injected) {
+        Ok(true) => app_state.cancel_requested.store(false, Ordering::SeqCst),
+        Ok(false) => {}
+        Err(StartError::Busy) => return,
+        Err(e) => {
+            log::warn(Event::ChainRejected { trigger, error: e.to_string() });
+            return;
+        }
+    }
+    log::info(Event::TriggerDetected { key: trigger });
+    let state = app_state.clone();
+    
+    std::thread::spawn(move || {
+        run_triggered(&state, index);
+        state.runs.lock().unwrap().finish(Some(index));
+    });
+}
+
+fn run_triggered(state: &AppState, index: usize) {
+    let macro_item = &state.config.macros[index];
+    let macro_clone = match state.config.expand(macro_item) {
+        Ok(m) => m,
+        Err(e) => {
+            log::error(Event::MacroExpandFailed { trigger: &macro_item.trigger, error: e.to_string() });
+            return;
+        }
+    };
+    // 순서 트리거는 마지막 조합을 수정 키와 함께 보낸다
+    let replay = match macro_item.passthrough {
+        Passthrough::After => trigger::steps(&macro_item.trigger).pop(),
+        _ => None,
+    };
+    
+    execute_macro(&macro_clone, Source::Trigger, &state.cancel_requested, |_, _| {});
+    if let Some(chord) = replay.filter(|_| !state.cancel_requested.load(Ordering::SeqCst)) {
+        replay_chords(&[chord]);
+    }
+}
+
+/// 삼켰던 조합을 눌렀다 떼서 돌려보낸다 (끊긴 순서, passthrough = "after").
+/// 지금 누르고 있지 않은 수정 키만 함께 눌렀다 뗀다
+fn replay_chords(chords: &[Chord]) {
+    for chord in chords {
+        let held = MODIFIERS.load(Ordering::SeqCst);
+        let modifiers = Chord::new(chord.modifiers & !held, chord.key.as_str()).modifier_keys();
+        let mut events: Vec<(&str, bool)> = modifiers.iter().map(|&m| (m, false)).collect();
+        events.extend([(chord.key.as_str(), false), (chord.key.as_str(), true)]);
+        events.extend(modifiers.iter().rev().map(|&m| (m, true)));
+        InputSystem::send_keys(&events);
+    }
+}
+
 fn scancode_to_key_name(scancode: u16, is_extended: bool) -> String {
     // SCANCODE 맵을 역으로 검색
     let keys = [
@@ -168,7 +432,12 @@ fn scancode_to_key_name(scancode: u16, is_extended: bool) -> String {
         ("f9", 0x43), ("f10", 0x44), ("f11", 0x57), ("f12", 0x58),
         // 특수키
         ("space", 0x39), ("enter", 0x1C), ("tab", 0x0F), ("esc", 0x01),
-        ("backspace", 0x0E), ("`", 0x29),
+        ("backspace", 0x0E), ("`", 0x29), ("capslock", 0x3A), ("scrolllock", 0x46),
+        // 기호
+        ("-", 0x0C), ("=", 0x0D), ("[", 0x1A), ("]", 0x1B), (";", 0x27), ("'", 0x28),
+        ("\\", 0x2B), (",", 0x33), (".", 0x34), ("/", 0x35),
+        // 수정 키 (좌우 구분 없음)
+        ("ctrl", 0x1D), ("shift", 0x2A), ("shift", 0x36), ("alt", 0x38),
     ];
     
     // 방향키 (extended)
@@ -191,30 +460,58 @@ fn scancode_to_key_name(scancode: u16, is_extended: bool) -> String {
     format!("unknown_{:X}", scancode)
 }
 
-fn execute_macro(macro_item: &shared::Macro) {
-    println!("  실행 시작: {}개 액션", macro_item.actions.len());
+/// 매크로 실행. 끝까지 실행되면 true, 취소되면 false
+fn execute_macro(
+    macro_item: &Macro,
+    source: Source,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(usize, usize),
+) -> bool {
+    let trigger = macro_item.trigger.as_str();
+    log::info(Event::MacroStarted { trigger, source, actions: macro_item.actions.len() });
     
-    for (i, action) in macro_item.actions.iter().enumerate() {
-        let pressed = InputSystem::press_key(&action.key);
-        if !pressed {
-            eprintln!("    ! 키 '{}' 를 찾을 수 없습니다!", action.key);
-            continue;
-        }
-        
-        if action.hold_ms > 0 {
-            spin_sleep::sleep(Duration::from_millis(action.hold_ms));
-        }
-        
-        InputSystem::release_key(&action.key);
-        
-        if action.delay_ms > 0 {
-            spin_sleep::sleep(Duration::from_millis(action.delay_ms));
-        }
-        
-        if (i + 1) % 10 == 0 {
-            println!("    진행: {}/{}", i + 1, macro_item.actions.len());
+    let clock = SystemClock::new();
+    let report = Executor::new(&clock, &SendInputBackend, cancel).run(macro_item, |event| match event {
+        ExecEvent::KeySent { key, keyup, drift_us } => {
+            log::debug(Event::KeyInjected { key, keyup, drift_us });
         }
+        ExecEvent::UnknownKey { key } => log::warn(Event::UnknownKey { key }),
+        ExecEvent::Progress { done, total } => on_progress(done, total),
+    });
+    
+    if report.completed {
+        log::info(Event::MacroFinished {
+            trigger,
+            elapsed_ms: report.timing.actual_us as f64 / 1000.0,
+        });
+    } else {
+        log::info(Event::MacroCancelled { trigger, done: report.done, total: report.total });
     }
+    log::info(Event::MacroTiming { trigger, timing: &report.timing });
     
-    println!("  ! 완료");
-}
\ No newline at end of file
+    report.completed
+}
+
+/// 키를 주입하지 않고 매크로를 반복 실행해 타이밍 통계만 출력
+fn measure(config: &MacroConfig, trigger: &str, runs: usize) {
+    let Some(macro_item) = config.get_macro(trigger) else {
+        log::error(Event::MacroNotFound { trigger });
+        return;
+    };
+    let macro_item = match config.expand(macro_item) {
+        Ok(m) => m,
+        Err(e) => {
+            log::error(Event::MacroExpandFailed { trigger, error: e.to_string() });
+            return;
+        }
+    };
+    
+    let clock = SystemClock::new();
+    let cancel = AtomicBool::new(false);
+    let executor = Executor::new(&clock, &NullBackend, &cancel);
+    
+    for run in 1..=runs {
+        let report = executor.run(&macro_item, |_| {});
+        log::info(Event::MeasureRun { trigger, run, timing: &report.timing });
+    }
+}
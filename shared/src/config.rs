@@ -1,29 +1,292 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use toml_edit::DocumentMut;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::dsl;
+use crate::gesture::Gesture;
+use crate::layer::Layer;
+
+mod backup;
+mod bundle;
+mod compose;
+mod conflicts;
+mod document;
+mod include;
+mod locate;
+mod migrate;
+mod params;
+
+pub use backup::{rotating_backup_path, BackupInfo, MAX_BACKUPS};
+pub use bundle::{BundleConflict, BundleError, MacroBundle, Resolution, BUNDLE_FORMAT, BUNDLE_VERSION};
+pub use compose::{ExpandError, MAX_CALL_DEPTH, MAX_EXPANDED_ACTIONS};
+pub use conflicts::{ConflictKind, TriggerConflict};
+pub use include::{IncludeConflict, IncludeError, ResolvedConfig};
+pub use locate::{locate, locate_in, user_config_dir, ConfigLocation, ConfigSource, CONFIG_ENV, CONFIG_FILE_NAME};
+pub use migrate::{backup_path, MigrateError, CONFIG_VERSION};
+pub use params::{substitute, ParamValue, Scope};
+
+/// 키를 어떻게 누르는지
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Press {
+    /// hold_ms 동안 눌렀다 뗀다
+    #[default]
+    Tap,
+    /// 누르기만 한다 (뗄 때까지 다음 액션과 겹친다)
+    Down,
+    /// 떼기만 한다
+    Up,
+}
+
+impl Press {
+    fn is_tap(&self) -> bool {
+        *self == Press::Tap
+    }
+}
+
+/// 트리거 키를 앱에도 넘길지
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Passthrough {
+    /// 트리거 키를 삼킨다
+    #[default]
+    Off,
+    /// 원래 키를 그대로 보내고 매크로를 실행한다 (누르고 있는 동안도 앱에 보인다)
+    Before,
+    /// 트리거 키를 삼켰다가 매크로가 끝난 뒤 눌렀다 떼서 보낸다
+    After,
+}
+
+impl Passthrough {
+    pub fn is_off(&self) -> bool {
+        *self == Passthrough::Off
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct MacroAction {
+    /// call, group 액션이면 비어 있다
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub key: String,
+    #[serde(default, skip_serializing_if = "Press::is_tap")]
+    pub press: Press,
     #[serde(default)]
     pub hold_ms: u64,
     #[serde(default)]
     pub delay_ms: u64,
+    /// 다른 매크로(id 또는 이름)를 이 자리에서 실행한다. delay_ms는 호출이 끝난 뒤 적용
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call: Option<String>,
+    /// 반복 횟수. 숫자나 "$count" 같은 매개변수
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<ParamValue>,
+    /// call 대상 매크로에 넘기는 인자
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub args: BTreeMap<String, ParamValue>,
+    /// 여러 액션을 묶어 함께 반복한다. delay_ms는 묶음이 끝난 뒤 적용
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group: Vec<MacroAction>,
+}
+
+impl MacroAction {
+    pub fn new(key: impl Into<String>, hold_ms: u64, delay_ms: u64) -> Self {
+        Self {
+            key: key.into(),
+            hold_ms,
+            delay_ms,
+            ..Self::default()
+        }
+    }
+    
+    /// 다른 매크로를 호출하는 액션
+    pub fn call(target: impl Into<String>, delay_ms: u64) -> Self {
+        Self {
+            delay_ms,
+            call: Some(target.into()),
+            ..Self::default()
+        }
+    }
+    
+    /// 누르기만 하거나 떼기만 하는 액션
+    pub fn press(key: impl Into<String>, press: Press, delay_ms: u64) -> Self {
+        Self {
+            key: key.into(),
+            press,
+            delay_ms,
+            ..Self::default()
+        }
+    }
+    
+    /// 액션 묶음
+    pub fn group(actions: Vec<MacroAction>, delay_ms: u64) -> Self {
+        Self {
+            delay_ms,
+            group: actions,
+            ..Self::default()
+        }
+    }
+    
+    /// 이 액션 뒤에 한 번 쉰다. 반복 액션의 delay는 반복마다 쉬므로 묶음으로 감싼다
+    pub fn append_delay(&mut self, delay_ms: u64) {
+        if self.repeat.is_some() {
+            let repeated = std::mem::take(self);
+            *self = MacroAction::group(vec![repeated], delay_ms);
+        } else {
+            self.delay_ms += delay_ms;
+        }
+    }
+    
+    /// 자신과 group 안의 모든 액션 (깊이 우선)
+    pub fn walk<'a>(actions: &'a [MacroAction], f: &mut impl FnMut(&'a MacroAction)) {
+        for action in actions {
+            f(action);
+            Self::walk(&action.group, f);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Macro {
+    /// 고정 식별자. Editor 명령은 인덱스 대신 이 값으로 매크로를 찾는다
+    #[serde(default)]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
     pub trigger: String,
+    /// script가 있으면 불러올 때 script에서 만들어진다
+    #[serde(default)]
     pub actions: Vec<MacroAction>,
+    /// actions 대신 쓰는 한 줄 문법 (예: "tap a 30ms; wait 100ms"). 저장할 때 actions로 다시 만든다
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub script: String,
     #[serde(default)]
     pub mode: u8,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// false면 Runner가 무시한다
+    #[serde(default = "default_enabled", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+    /// 매개변수와 기본값. 액션의 key, call, repeat, args에서 $이름으로 쓴다
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, ParamValue>,
+    /// 트리거를 누르는 방식 (예: gesture = "release", gesture = { long_press = 500 })
+    #[serde(default, skip_serializing_if = "Gesture::is_press")]
+    pub gesture: Gesture,
+    /// 트리거 키를 앱에도 넘길지 (예: passthrough = "before")
+    #[serde(default, skip_serializing_if = "Passthrough::is_off")]
+    pub passthrough: Passthrough,
+    /// 이 레이어가 켜져 있을 때만 실행한다. 비어 있으면 기본 레이어 (항상)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub layer: String,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+impl Macro {
+    /// 새 id를 가진 빈 매크로
+    pub fn new(trigger: impl Into<String>) -> Self {
+        Self {
+            id: new_macro_id(),
+            name: String::new(),
+            trigger: trigger.into(),
+            actions: Vec::new(),
+            script: String::new(),
+            mode: 0,
+            description: String::new(),
+            tags: Vec::new(),
+            enabled: true,
+            params: BTreeMap::new(),
+            gesture: Gesture::Press,
+            passthrough: Passthrough::Off,
+            layer: String::new(),
+        }
+    }
+    
+    /// 표시용 이름 (이름이 없으면 트리거)
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() { &self.trigger } else { &self.name }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+/// 읽을 때 붙인 id (macro-N)인지. 예전 변환도 이 형식으로 파일에 적었다
+pub(crate) fn is_generated_id(id: &str) -> bool {
+    id.strip_prefix("macro-").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// 겹치지 않는 새 매크로 id
+pub fn new_macro_id() -> String {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    format!("m{:x}{:02x}", nanos, COUNTER.fetch_add(1, Ordering::Relaxed) & 0xff)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MacroConfig {
+    /// 스키마 버전. 저장할 때는 항상 CONFIG_VERSION으로 기록된다
+    #[serde(default)]
+    pub version: u32,
+    /// 함께 불러올 파일 (이 파일 기준 상대 경로). 여기 정의된 매크로가 우선한다
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// include만 모아 둔 파일처럼 없어도 된다
+    #[serde(default)]
     pub macros: Vec<Macro>,
     #[serde(default = "default_toggle_key")]
     pub toggle_key: String,
+    /// 매크로가 누른 키로 다른 매크로를 실행한다. 기본은 주입한 키를 트리거로 보지 않는다
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub chain_triggers: bool,
+    /// 순서 트리거 ("f12 g 1")의 다음 키를 기다리는 시간
+    #[serde(default = "default_sequence_timeout", skip_serializing_if = "is_default_sequence_timeout")]
+    pub sequence_timeout_ms: u64,
+    /// 키 바꾸기 (원래 키 → 보낼 키). 예: capslock = "esc". 두 키를 서로 적으면 맞바꾼다
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remap: BTreeMap<String, String>,
+    /// 레이어 키와 방식. 매크로는 layer 값으로 속할 레이어를 정한다
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<Layer>,
+}
+
+impl Default for MacroConfig {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            include: Vec::new(),
+            macros: Vec::new(),
+            toggle_key: String::new(),
+            chain_triggers: false,
+            sequence_timeout_ms: default_sequence_timeout(),
+            remap: BTreeMap::new(),
+            layers: Vec::new(),
+        }
+    }
+}
+
+fn default_sequence_timeout() -> u64 {
+    1000
+}
+
+fn is_default_sequence_timeout(ms: &u64) -> bool {
+    *ms == default_sequence_timeout()
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 fn default_toggle_key() -> String {
@@ -31,19 +294,162 @@ fn default_toggle_key() -> String {
 }
 
 impl MacroConfig {
+    /// 설정 로드. 이전 버전 파일은 원본을 백업한 뒤 현재 버전으로 변환해 다시 저장한다 (주석 유지)
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
         let content = fs::read_to_string(path)?;
-        let config: MacroConfig = toml::from_str(&content)?;
+        let (config, found) = Self::parse_versioned(&content)?;
+        
+        if found < CONFIG_VERSION {
+            fs::copy(path, backup_path(path, found))?;
+            config.save(path)?;
+        }
+        
         Ok(config)
     }
     
+    /// include까지 모두 펼쳐 읽는다 (Runner용). 파일 자체의 내용만 필요하면 load를 쓴다
+    pub fn load_resolved<P: AsRef<Path>>(path: P) -> Result<ResolvedConfig, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let root = Self::load(path)?;
+        Ok(include::resolve(path, root)?)
+    }
+    
+    /// 문자열에서 읽기. 이전 버전 문서는 메모리에서만 변환하고 파일은 건드리지 않는다
+    pub fn parse(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::parse_versioned(content)?.0)
+    }
+    
+    /// 변환된 설정과 원래 문서 버전
+    fn parse_versioned(content: &str) -> Result<(Self, u32), Box<dyn std::error::Error>> {
+        let mut table: toml::Table = content.parse()?;
+        let found = migrate::upgrade(&mut table)?;
+        let mut config: MacroConfig = table.try_into()?;
+        config.assign_missing_ids();
+        config.parse_scripts()?;
+        Ok((config, found))
+    }
+    
+    /// 직접 작성해 id가 없거나 겹치는 매크로에 id를 붙인다 (macro-N).
+    /// 다시 읽어도 같은 id가 붙도록 순서대로 번호를 매긴다
+    pub fn assign_missing_ids(&mut self) {
+        let mut seen = HashSet::new();
+        let mut missing = Vec::new();
+        for (i, m) in self.macros.iter().enumerate() {
+            if m.id.is_empty() || !seen.insert(m.id.clone()) {
+                missing.push(i);
+            }
+        }
+        
+        let mut n = 0;
+        for i in missing {
+            let id = loop {
+                n += 1;
+                let candidate = format!("macro-{}", n);
+                if !seen.contains(&candidate) {
+                    break candidate;
+                }
+            };
+            seen.insert(id.clone());
+            self.macros[i].id = id;
+        }
+    }
+    
+    /// script로 쓴 매크로의 actions를 만든다
+    fn parse_scripts(&mut self) -> Result<(), String> {
+        for m in self.macros.iter_mut().filter(|m| !m.script.is_empty()) {
+            if !m.actions.is_empty() {
+                return Err(format!("{}: script와 actions를 함께 쓸 수 없습니다", m.display_name()));
+            }
+            m.actions = dsl::parse(&m.script).map_err(|e| format!("{}: script {}", m.display_name(), e))?;
+        }
+        Ok(())
+    }
+    
+    /// 설정 저장. 기존 파일이 있으면 바뀐 값만 반영해 주석, 키 순서, 서식을 유지한다.
+    /// 임시 파일에 쓴 뒤 교체하며, 이전 내용은 최대 MAX_BACKUPS개까지 보관된다
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
-        let content = toml::to_string_pretty(self)?;
-        fs::write(path, content)?;
+        let path = path.as_ref();
+        let updated = self.to_document()?;
+        
+        let existing = fs::read_to_string(path).ok();
+        let content = match existing.as_deref().and_then(|text| text.parse::<DocumentMut>().ok()) {
+            Some(mut doc) => {
+                // 기존 파일을 같은 방식으로 직렬화한 것과 비교해 사용자가 지운 키만 지운다
+                let base = existing.as_deref().and_then(Self::base_document);
+                document::merge_document(&mut doc, base.as_ref(), &updated);
+                doc.to_string()
+            }
+            None => updated.to_string(),
+        };
+        
+        // 사용자 설정 폴더에 처음 저장하는 경우
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        backup::write_with_backup(path, &content)?;
         Ok(())
     }
     
+    /// 저장할 때 쓰는 문서. script로 쓴 매크로는 actions 대신 script를 적는다
+    fn to_document(&self) -> Result<DocumentMut, Box<dyn std::error::Error>> {
+        let mut config = self.clone();
+        config.version = CONFIG_VERSION;
+        // script로 쓴 매크로는 Editor에서 actions를 바꿨을 때만 다시 script로 적는다 (주석, 줄바꿈 유지)
+        for m in config.macros.iter_mut().filter(|m| !m.script.is_empty()) {
+            if dsl::parse(&m.script).ok().as_ref() != Some(&m.actions) {
+                m.script = dsl::print(&m.actions);
+            }
+            m.actions.clear();
+        }
+        let mut updated: DocumentMut = toml::to_string_pretty(&config)?.parse()?;
+        if let Some(macros) = updated.get_mut("macros").and_then(|m| m.as_array_of_tables_mut()) {
+            for m in macros.iter_mut().filter(|m| m.contains_key("script")) {
+                m.remove("actions");
+            }
+        }
+        Ok(updated)
+    }
+    
+    /// 기존 문서를 읽어 다시 직렬화한 것. 버전 변환이 더한 키는 빼서 새 내용과 같아도 적히게 한다
+    fn base_document(text: &str) -> Option<DocumentMut> {
+        let before: toml::Table = text.parse().ok()?;
+        let mut after = before.clone();
+        migrate::upgrade(&mut after).ok()?;
+        let mut base = Self::parse(text).ok()?.to_document().ok()?;
+        document::forget_added(base.as_table_mut(), &before, &after);
+        Some(base)
+    }
+    
+    /// 보관 중인 이전 버전 목록 (최근 것부터)
+    pub fn backups<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<BackupInfo>> {
+        backup::list_backups(path.as_ref())
+    }
+    
+    /// 백업을 현재 설정으로 되돌린다. 되돌리기 전 내용도 백업으로 남는다
+    pub fn restore_backup<P: AsRef<Path>>(path: P, index: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(rotating_backup_path(path, index))?;
+        
+        // 깨진 백업으로 덮어쓰지 않도록 먼저 읽어 본다
+        Self::parse(&content)?;
+        
+        backup::write_with_backup(path, &content)?;
+        Self::load(path)
+    }
+    
+    /// 트리거로 찾기 (비활성 매크로 제외)
+    /// 조합키 표기 순서는 상관없다 ("shift+ctrl+1" == "ctrl+shift+1")
     pub fn get_macro(&self, trigger: &str) -> Option<&Macro> {
-        self.macros.iter().find(|m| m.trigger == trigger)
+        let trigger = crate::trigger::normalize(trigger);
+        self.macros.iter().find(|m| m.enabled && crate::trigger::normalize(&m.trigger) == trigger)
+    }
+    
+    pub fn get_macro_by_id(&self, id: &str) -> Option<&Macro> {
+        self.macros.iter().find(|m| m.id == id)
+    }
+    
+    pub fn get_macro_by_id_mut(&mut self, id: &str) -> Option<&mut Macro> {
+        self.macros.iter_mut().find(|m| m.id == id)
     }
 }
\ No newline at end of file
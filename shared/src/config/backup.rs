@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// 보관할 이전 버전 개수
+pub const MAX_BACKUPS: usize = 5;
+
+/// 백업 파일 정보. index 1이 가장 최근
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub index: usize,
+    pub path: PathBuf,
+    /// 수정 시각 (유닉스 초)
+    pub modified_secs: u64,
+    pub size: u64,
+}
+
+/// config.toml → config.toml.bak.N
+pub fn rotating_backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak.{}", index));
+    path.with_file_name(name)
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// 기존 파일을 백업으로 돌리고 새 내용을 원자적으로 기록한다.
+/// 내용이 같으면 아무것도 하지 않는다
+pub fn write_with_backup(path: &Path, content: &str) -> io::Result<()> {
+    match fs::read_to_string(path) {
+        Ok(existing) if existing == content => return Ok(()),
+        Ok(_) => rotate(path)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    write_atomic(path, content)
+}
+
+/// 임시 파일에 끝까지 쓴 뒤 rename. 읽는 쪽은 이전 내용이나 새 내용 중 하나만 보게 된다
+pub fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let tmp = temp_path(path);
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// .bak.1 → .bak.2 ... 순으로 밀고, 현재 파일을 .bak.1로 복사
+fn rotate(path: &Path) -> io::Result<()> {
+    let oldest = rotating_backup_path(path, MAX_BACKUPS);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for index in (1..MAX_BACKUPS).rev() {
+        let from = rotating_backup_path(path, index);
+        if from.exists() {
+            fs::rename(&from, rotating_backup_path(path, index + 1))?;
+        }
+    }
+    // 복사이므로 원본 경로는 항상 존재한다
+    fs::copy(path, rotating_backup_path(path, 1))?;
+    Ok(())
+}
+
+/// 존재하는 백업 목록 (최근 것부터)
+pub fn list_backups(path: &Path) -> io::Result<Vec<BackupInfo>> {
+    let mut backups = Vec::new();
+    for index in 1..=MAX_BACKUPS {
+        let backup = rotating_backup_path(path, index);
+        let meta = match fs::metadata(&backup) {
+            Ok(m) => m,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        let modified_secs = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        backups.push(BackupInfo {
+            index,
+            path: backup,
+            modified_secs,
+            size: meta.len(),
+        });
+    }
+    Ok(backups)
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{new_macro_id, Macro, MacroAction, MacroConfig};
+use crate::gesture::Gesture;
+use crate::trigger;
+
+/// 번들 파일 표시
+pub const BUNDLE_FORMAT: &str = "keym-macros";
+/// 번들 스키마 버전. 이보다 새 번들은 읽지 않는다
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// 다른 설정으로 옮길 수 있는 매크로 묶음 (JSON)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroBundle {
+    pub format: String,
+    pub version: u32,
+    /// 내보낸 KeyM 버전
+    #[serde(default)]
+    pub app_version: String,
+    /// 내보낸 시각 (유닉스 초)
+    #[serde(default)]
+    pub exported_at: u64,
+    #[serde(default)]
+    pub macros: Vec<Macro>,
+}
+
+/// 가져올 매크로와 트리거, 레이어, 누르는 방식이 모두 같은 기존 매크로 (또는 번들 앞쪽 매크로)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundleConflict {
+    /// 번들 안의 id
+    pub id: String,
+    pub name: String,
+    pub trigger: String,
+    pub existing_id: String,
+    pub existing_name: String,
+    /// 겹치는 매크로가 설정이 아니라 같은 번들 앞쪽에 있다
+    pub in_bundle: bool,
+}
+
+/// 겹친 트리거 처리 방법 (번들 매크로 id별로 고른다)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Resolution {
+    /// 가져오지 않는다. 번들 안의 call은 기존 매크로를 부른다
+    Skip,
+    /// 다른 트리거로 바꿔 함께 둔다
+    Rename { trigger: String },
+    /// 기존 매크로 자리에 덮어쓴다 (기존 id 유지)
+    Replace,
+}
+
+#[derive(Debug)]
+pub enum BundleError {
+    Invalid(String),
+    Format(String),
+    Version(u32),
+    /// 처리 방법을 고르지 않은 충돌
+    Unresolved { trigger: String },
+    /// 바꾼 트리거가 다시 겹침
+    Taken { trigger: String },
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::Invalid(message) => write!(f, "번들을 읽을 수 없습니다: {}", message),
+            BundleError::Format(format) => write!(f, "KeyM 매크로 번들이 아닙니다: {}", format),
+            BundleError::Version(version) => write!(
+                f,
+                "더 새로운 버전의 번들입니다 (번들 {}, 지원 {})",
+                version, BUNDLE_VERSION
+            ),
+            BundleError::Unresolved { trigger } => write!(f, "트리거 충돌을 처리하지 않았습니다: {}", trigger),
+            BundleError::Taken { trigger } => write!(f, "이미 사용 중인 트리거입니다: {}", trigger),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+impl MacroBundle {
+    /// 고른 매크로와, 그 매크로가 call로 부르는 매크로를 함께 묶는다
+    pub fn export(config: &MacroConfig, ids: &[String]) -> Self {
+        let mut included = HashSet::new();
+        let mut queue: Vec<&Macro> = ids.iter().filter_map(|id| config.get_macro_by_id(id)).collect();
+        while let Some(m) = queue.pop() {
+            if !included.insert(m.id.as_str()) {
+                continue;
+            }
+            MacroAction::walk(&m.actions, &mut |action| {
+                if let Some(callee) = action.call.as_deref().and_then(|t| config.find_callee(t)) {
+                    queue.push(callee);
+                }
+            });
+        }
+
+        Self {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            // 설정에 있던 순서 유지
+            macros: config.macros.iter().filter(|m| included.contains(m.id.as_str())).cloned().collect(),
+        }
+    }
+
+    pub fn parse(json: &str) -> Result<Self, BundleError> {
+        let bundle: Self = serde_json::from_str(json).map_err(|e| BundleError::Invalid(e.to_string()))?;
+        if bundle.format != BUNDLE_FORMAT {
+            return Err(BundleError::Format(bundle.format));
+        }
+        if bundle.version > BUNDLE_VERSION {
+            return Err(BundleError::Version(bundle.version));
+        }
+        Ok(bundle)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("번들은 항상 JSON으로 바꿀 수 있다")
+    }
+
+    /// 활성 매크로끼리 함께 실행될 수 없는 경우. 설정과 먼저 비교하고, 번들 안의 중복도 찾는다
+    pub fn conflicts(&self, config: &MacroConfig) -> Vec<BundleConflict> {
+        let enabled: Vec<&Macro> = self.macros.iter().filter(|m| m.enabled).collect();
+        let mut conflicts = Vec::new();
+        for (i, m) in enabled.iter().enumerate() {
+            let key = slot(m);
+            let existing = config
+                .macros
+                .iter()
+                .find(|e| e.enabled && slot(e) == key)
+                .map(|e| (e, false))
+                .or_else(|| enabled[..i].iter().find(|e| slot(e) == key).map(|e| (*e, true)));
+            if let Some((existing, in_bundle)) = existing {
+                conflicts.push(BundleConflict {
+                    id: m.id.clone(),
+                    name: m.display_name().to_string(),
+                    trigger: m.trigger.clone(),
+                    existing_id: existing.id.clone(),
+                    existing_name: existing.display_name().to_string(),
+                    in_bundle,
+                });
+            }
+        }
+        conflicts
+    }
+}
+
+/// 겹침을 가르는 값. 트리거 조합 표기 순서는 무시하고, 레이어나 누르는 방식이 다르면 함께 쓸 수 있다
+fn slot(m: &Macro) -> (String, &str, Gesture) {
+    (trigger::normalize(&m.trigger), m.layer.as_str(), m.gesture)
+}
+
+impl MacroConfig {
+    /// 번들 매크로를 추가한다. 모든 충돌에 처리 방법이 있어야 한다.
+    /// 기존 id와 겹치는 id는 새로 만들고, 번들 안의 call도 함께 고친다. 추가되거나 바뀐 매크로 id를 돌려준다
+    pub fn import_bundle(
+        &mut self,
+        bundle: MacroBundle,
+        resolutions: &BTreeMap<String, Resolution>,
+    ) -> Result<Vec<String>, BundleError> {
+        let conflicts: HashMap<String, BundleConflict> =
+            bundle.conflicts(self).into_iter().map(|c| (c.id.clone(), c)).collect();
+
+        let mut taken: HashSet<String> = self.macros.iter().map(|m| m.id.clone()).collect();
+        let mut slots: HashSet<(String, String, Gesture)> = self
+            .macros
+            .iter()
+            .chain(&bundle.macros)
+            .filter(|m| m.enabled)
+            .map(|m| {
+                let (trigger, layer, gesture) = slot(m);
+                (trigger, layer.to_string(), gesture)
+            })
+            .collect();
+
+        // 번들 id → 설정에서 쓸 id
+        let mut ids = HashMap::new();
+        // 건너뛰거나 덮어써 없어진 번들 매크로 → 대신 부를 번들 매크로 (번들 안의 중복)
+        let mut aliases = Vec::new();
+        // (매크로, 덮어쓸 기존 id)
+        let mut incoming: Vec<(Macro, Option<String>)> = Vec::new();
+        for mut m in bundle.macros {
+            let replace = match conflicts.get(&m.id) {
+                None => None,
+                Some(conflict) => match resolutions.get(&m.id) {
+                    None => return Err(BundleError::Unresolved { trigger: conflict.trigger.clone() }),
+                    Some(Resolution::Skip) if conflict.in_bundle => {
+                        aliases.push((m.id, conflict.existing_id.clone()));
+                        continue;
+                    }
+                    Some(Resolution::Skip) => {
+                        ids.insert(m.id, conflict.existing_id.clone());
+                        continue;
+                    }
+                    Some(Resolution::Replace) if conflict.in_bundle => {
+                        // 앞쪽 번들 매크로 자리를 이어받는다
+                        let earlier = ids.get(&conflict.existing_id).cloned().unwrap_or_default();
+                        let position = incoming.iter().position(|(e, _)| e.id == earlier);
+                        aliases.push((conflict.existing_id.clone(), m.id.clone()));
+                        position.and_then(|i| incoming.remove(i).1)
+                    }
+                    Some(Resolution::Replace) => Some(conflict.existing_id.clone()),
+                    Some(Resolution::Rename { trigger }) => {
+                        let key = (trigger::normalize(trigger), m.layer.clone(), m.gesture);
+                        if trigger.trim().is_empty() || !slots.insert(key) {
+                            return Err(BundleError::Taken { trigger: trigger.clone() });
+                        }
+                        m.trigger = trigger.clone();
+                        None
+                    }
+                },
+            };
+
+            let id = match &replace {
+                Some(existing) => existing.clone(),
+                None if taken.contains(&m.id) || m.id.is_empty() => new_macro_id(),
+                None => m.id.clone(),
+            };
+            taken.insert(id.clone());
+            ids.insert(m.id.clone(), id.clone());
+            m.id = id;
+            incoming.push((m, replace));
+        }
+
+        for (from, to) in aliases {
+            let id = ids.get(&to).cloned().unwrap_or(to);
+            ids.insert(from, id);
+        }
+
+        let mut imported = Vec::with_capacity(incoming.len());
+        for (mut m, replace) in incoming {
+            rename_calls(&mut m.actions, &ids);
+            imported.push(m.id.clone());
+            match replace.and_then(|id| self.macros.iter().position(|existing| existing.id == id)) {
+                Some(i) => self.macros[i] = m,
+                None => self.macros.push(m),
+            }
+        }
+        Ok(imported)
+    }
+}
+
+fn rename_calls(actions: &mut [MacroAction], ids: &HashMap<String, String>) {
+    for action in actions {
+        if let Some(id) = action.call.as_ref().and_then(|target| ids.get(target)) {
+            action.call = Some(id.clone());
+        }
+        rename_calls(&mut action.group, ids);
+    }
+}
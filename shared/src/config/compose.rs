@@ -0,0 +1,280 @@
+use std::fmt;
+
+use super::params::{self, ParamValue, Scope};
+use super::{Macro, MacroAction, MacroConfig};
+
+/// 실행 시 call 중첩 한도 (최상위 매크로 제외)
+pub const MAX_CALL_DEPTH: usize = 8;
+
+/// 펼친 액션 수 한도. repeat가 커도 메모리를 다 쓰지 않도록 넘으면 오류
+pub const MAX_EXPANDED_ACTIONS: usize = 100_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpandError {
+    /// 호출 대상이 없음
+    Missing { from: String, target: String },
+    /// 자기 자신으로 돌아오는 호출 (a → b → a)
+    Cycle { chain: Vec<String> },
+    /// MAX_CALL_DEPTH를 넘는 중첩
+    TooDeep { chain: Vec<String> },
+    /// 선언하지 않은 매개변수 사용
+    UnknownParam { from: String, name: String },
+    /// 호출된 매크로에 없는 인자
+    UnknownArg { from: String, target: String, name: String },
+    /// 반복 횟수가 숫자가 아님
+    InvalidRepeat { from: String, value: String },
+    /// MAX_EXPANDED_ACTIONS를 넘는 펼친 결과
+    TooLong { from: String },
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandError::Missing { from, target } => {
+                write!(f, "{}: 호출할 매크로를 찾을 수 없습니다: {}", from, target)
+            }
+            ExpandError::Cycle { chain } => write!(f, "매크로 호출이 순환합니다: {}", chain.join(" → ")),
+            ExpandError::TooDeep { chain } => write!(
+                f,
+                "매크로 호출이 너무 깊습니다 (최대 {}단계): {}",
+                MAX_CALL_DEPTH,
+                chain.join(" → ")
+            ),
+            ExpandError::UnknownParam { from, name } => {
+                write!(f, "{}: 선언하지 않은 매개변수입니다: ${}", from, name)
+            }
+            ExpandError::UnknownArg { from, target, name } => {
+                write!(f, "{}: {}에는 매개변수 {}가 없습니다", from, target, name)
+            }
+            ExpandError::InvalidRepeat { from, value } => {
+                write!(f, "{}: 반복 횟수가 숫자가 아닙니다: {}", from, value)
+            }
+            ExpandError::TooLong { from } => {
+                write!(f, "{}: 펼친 액션이 너무 많습니다 (최대 {}개)", from, MAX_EXPANDED_ACTIONS)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExpandError {}
+
+/// 순환 검사에서 매크로의 방문 상태
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    New,
+    Active,
+    Done,
+}
+
+impl MacroConfig {
+    /// call 대상 찾기. id가 먼저, 없으면 이름. 비활성 매크로도 호출할 수 있다 (공용 시퀀스용)
+    pub fn find_callee(&self, target: &str) -> Option<&Macro> {
+        self.get_macro_by_id(target)
+            .or_else(|| self.macros.iter().find(|m| m.name == target))
+    }
+
+    /// 실행하지 않고 찾을 수 있는 문제를 모두 보고한다 (없는 대상, 순환, 매개변수).
+    /// 대상이 매개변수로 정해지는 call은 실행할 때 검사한다
+    pub fn check_macros(&self) -> Vec<ExpandError> {
+        let mut errors = Vec::new();
+        for m in &self.macros {
+            let from = || m.display_name().to_string();
+            let declared = |name: &str| m.params.contains_key(name).then(String::new);
+
+            let mut all = Vec::new();
+            MacroAction::walk(&m.actions, &mut |action| all.push(action));
+            for action in all {
+                let repeat = match &action.repeat {
+                    Some(ParamValue::Text(text)) => Some(text),
+                    _ => None,
+                };
+                let args = action.args.values().filter_map(|value| match value {
+                    ParamValue::Text(text) => Some(text),
+                    ParamValue::Number(_) => None,
+                });
+                for text in [Some(&action.key), action.call.as_ref(), repeat].into_iter().flatten().chain(args) {
+                    if let Err(name) = params::substitute(text, declared) {
+                        errors.push(ExpandError::UnknownParam { from: from(), name });
+                    }
+                }
+
+                let Some(target) = action.call.as_deref().filter(|t| !t.contains('$')) else { continue };
+                let Some(callee) = self.find_callee(target) else {
+                    errors.push(ExpandError::Missing { from: from(), target: target.to_string() });
+                    continue;
+                };
+                for name in action.args.keys().filter(|name| !callee.params.contains_key(*name)) {
+                    errors.push(ExpandError::UnknownArg {
+                        from: from(),
+                        target: target.to_string(),
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+
+        // 각 매크로를 한 번씩만 방문한다. 방문 중인 매크로로 돌아오는 호출 하나가 순환 하나
+        let mut visits = vec![Visit::New; self.macros.len()];
+        for start in 0..self.macros.len() {
+            self.find_cycles(start, &mut visits, &mut Vec::new(), &mut errors);
+        }
+        errors
+    }
+
+    /// 깊이 우선 탐색. path는 지금 방문 중인 호출 경로
+    fn find_cycles(&self, current: usize, visits: &mut [Visit], path: &mut Vec<usize>, errors: &mut Vec<ExpandError>) {
+        if visits[current] != Visit::New {
+            return;
+        }
+        visits[current] = Visit::Active;
+        path.push(current);
+
+        let mut callees = Vec::new();
+        MacroAction::walk(&self.macros[current].actions, &mut |action| {
+            if let Some(next) = action.call.as_deref().and_then(|target| self.callee_index(target))
+                && !callees.contains(&next)
+            {
+                callees.push(next);
+            }
+        });
+        for next in callees {
+            match visits[next] {
+                Visit::New => self.find_cycles(next, visits, path, errors),
+                Visit::Active => {
+                    let from = path.iter().position(|&i| i == next).expect("방문 중인 매크로는 경로에 있다");
+                    let chain = path[from..]
+                        .iter()
+                        .chain([&next])
+                        .map(|&i| self.macros[i].display_name().to_string())
+                        .collect();
+                    errors.push(ExpandError::Cycle { chain });
+                }
+                Visit::Done => {}
+            }
+        }
+
+        path.pop();
+        visits[current] = Visit::Done;
+    }
+
+    fn callee_index(&self, target: &str) -> Option<usize> {
+        self.macros.iter().position(|m| m.id == target)
+            .or_else(|| self.macros.iter().position(|m| m.name == target))
+    }
+
+    /// call, repeat, group을 펼치고 매개변수를 값으로 바꾼 사본. 실행 직전에 쓴다
+    pub fn expand(&self, macro_item: &Macro) -> Result<Macro, ExpandError> {
+        let mut actions = Vec::with_capacity(macro_item.actions.len());
+        self.expand_into(&macro_item.actions, &mut vec![macro_item], &macro_item.params, &mut actions)?;
+        Ok(Macro {
+            actions,
+            ..macro_item.clone()
+        })
+    }
+
+    fn expand_into<'a>(
+        &'a self,
+        list: &'a [MacroAction],
+        chain: &mut Vec<&'a Macro>,
+        scope: &Scope,
+        actions: &mut Vec<MacroAction>,
+    ) -> Result<(), ExpandError> {
+        let names = |chain: &[&Macro]| chain.iter().map(|m| m.display_name().to_string()).collect();
+        let macro_item = *chain.last().expect("호출 경로는 비어 있지 않다");
+        let from = || macro_item.display_name().to_string();
+        let resolve = |text: &str| {
+            params::substitute_in(text, scope).map_err(|name| ExpandError::UnknownParam { from: from(), name })
+        };
+
+        for action in list {
+            let repeat = match &action.repeat {
+                None => 1,
+                Some(ParamValue::Number(n)) => *n,
+                Some(ParamValue::Text(text)) => {
+                    let value = resolve(text)?;
+                    value.trim().parse().map_err(|_| ExpandError::InvalidRepeat { from: from(), value })?
+                }
+            };
+            // 한 번도 실행하지 않는 액션도 뒤에 쉬는 시간은 남긴다 (빈 펼침과 같이 앞 액션 뒤에)
+            if repeat == 0 {
+                let end = actions.len();
+                delay_last(actions, end, action.delay_ms, 1);
+                continue;
+            }
+
+            if !action.group.is_empty() {
+                for done in 0..repeat {
+                    let before = actions.len();
+                    self.expand_into(&action.group, chain, scope, actions)?;
+                    if !delay_last(actions, before, action.delay_ms, repeat - done) {
+                        break;
+                    }
+                }
+                continue;
+            }
+
+            let Some(target) = &action.call else {
+                let resolved = MacroAction {
+                    key: resolve(&action.key)?,
+                    press: action.press,
+                    hold_ms: action.hold_ms,
+                    delay_ms: action.delay_ms,
+                    ..MacroAction::default()
+                };
+                if repeat > (MAX_EXPANDED_ACTIONS - actions.len()) as u64 {
+                    return Err(ExpandError::TooLong { from: from() });
+                }
+                actions.extend((0..repeat).map(|_| resolved.clone()));
+                continue;
+            };
+
+            let target = resolve(target)?;
+            let callee = self.find_callee(&target).ok_or_else(|| ExpandError::Missing {
+                from: from(),
+                target: target.clone(),
+            })?;
+
+            // 호출된 매크로의 기본값 위에 인자를 덮는다. 문자열 인자는 호출한 쪽 변수로 치환
+            let mut callee_scope = callee.params.clone();
+            for (name, value) in &action.args {
+                if !callee.params.contains_key(name) {
+                    return Err(ExpandError::UnknownArg { from: from(), target, name: name.clone() });
+                }
+                let value = match value {
+                    ParamValue::Text(text) => ParamValue::Text(resolve(text)?),
+                    number => number.clone(),
+                };
+                callee_scope.insert(name.clone(), value);
+            }
+
+            let cycle = chain.iter().any(|m| m.id == callee.id);
+            chain.push(callee);
+            if cycle {
+                return Err(ExpandError::Cycle { chain: names(chain) });
+            }
+            if chain.len() > MAX_CALL_DEPTH + 1 {
+                return Err(ExpandError::TooDeep { chain: names(chain) });
+            }
+            for done in 0..repeat {
+                let before = actions.len();
+                self.expand_into(&callee.actions, chain, &callee_scope, actions)?;
+                if !delay_last(actions, before, action.delay_ms, repeat - done) {
+                    break;
+                }
+            }
+            chain.pop();
+        }
+        Ok(())
+    }
+}
+
+/// call, group 액션의 delay는 펼친 시퀀스가 끝날 때마다 쉰다.
+/// 펼친 결과가 비어 있으면 남은 반복의 delay를 모두 앞 액션 뒤에 붙이고 false (더 펼칠 것이 없다)
+fn delay_last(actions: &mut [MacroAction], before: usize, delay_ms: u64, remaining: u64) -> bool {
+    let expanded = actions.len() > before;
+    let delay = if expanded { delay_ms } else { delay_ms.saturating_mul(remaining) };
+    if let Some(last) = actions.last_mut() {
+        last.delay_ms = last.delay_ms.saturating_add(delay);
+    }
+    expanded
+}
@@ -0,0 +1,167 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+
+use super::{Macro, MacroConfig, Passthrough, Press};
+use crate::trigger::{self, Chord};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// 같은 트리거와 누르는 방식. 먼저 정의된 매크로만 실행된다
+    Duplicate,
+    /// 토글 키가 먼저 잡는 트리거 ("f8" 토글 키는 "shift+f8"도 잡는다). 실행되지 않는다
+    ToggleKey,
+    /// 수정 키 단독 트리거가 그 수정 키를 쓰는 조합을 가로챈다 ("ctrl"이 "ctrl+1"보다 먼저 잡힘)
+    Shadowed,
+    /// 매크로가 자기 트리거를 눌러 다시 실행될 수 있다 (chain_triggers가 켜져 있을 때만)
+    SelfTrigger,
+    /// 순서 트리거의 앞부분이 다른 트리거라서 끝까지 갈 수 없다 ("f12 g"가 "f12 g 1"을 막음)
+    AmbiguousSequence,
+}
+
+/// 트리거 문제 하나. macro_ids[0]이 영향을 받는 매크로, 나머지는 원인이 된 매크로
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TriggerConflict {
+    pub kind: ConflictKind,
+    pub trigger: String,
+    pub macro_ids: Vec<String>,
+    /// 표시용 이름 (macro_ids와 같은 순서)
+    pub names: Vec<String>,
+}
+
+impl TriggerConflict {
+    fn new(kind: ConflictKind, trigger: &str, macros: &[&Macro]) -> Self {
+        Self {
+            kind,
+            trigger: trigger.to_string(),
+            macro_ids: macros.iter().map(|m| m.id.clone()).collect(),
+            names: macros.iter().map(|m| m.display_name().to_string()).collect(),
+        }
+    }
+}
+
+impl fmt::Display for TriggerConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |i: usize| self.names.get(i).map(String::as_str).unwrap_or_default();
+        match self.kind {
+            ConflictKind::Duplicate => write!(
+                f,
+                "{}: 트리거 {}가 {}와 겹쳐 실행되지 않습니다",
+                name(0),
+                self.trigger,
+                name(1)
+            ),
+            ConflictKind::ToggleKey => write!(f, "{}: 트리거 {}가 토글 키와 같습니다", name(0), self.trigger),
+            ConflictKind::Shadowed => write!(
+                f,
+                "{}: 트리거 {}를 {}의 트리거가 먼저 가로챕니다",
+                name(0),
+                self.trigger,
+                name(1)
+            ),
+            ConflictKind::SelfTrigger => {
+                write!(f, "{}: 실행 중 자기 트리거 {}를 눌러 다시 실행될 수 있습니다", name(0), self.trigger)
+            }
+            ConflictKind::AmbiguousSequence => write!(
+                f,
+                "{}: 순서 트리거 {}와 {}의 트리거가 앞부분이 겹칩니다",
+                name(0),
+                self.trigger,
+                name(1)
+            ),
+        }
+    }
+}
+
+impl MacroConfig {
+    /// 활성 매크로의 트리거 문제를 모두 찾는다. 조합키 표기 순서는 상관없다
+    pub fn trigger_conflicts(&self) -> Vec<TriggerConflict> {
+        let toggle = (!self.toggle_key.is_empty()).then(|| Chord::parse(&self.toggle_key));
+        let all: Vec<(&Macro, Vec<Chord>)> = self
+            .macros
+            .iter()
+            .filter(|m| m.enabled)
+            .map(|m| (m, trigger::steps(&m.trigger)))
+            .collect();
+
+        // 기본 레이어 매크로는 모든 레이어에서 함께 쓰인다. 다른 레이어끼리는 동시에 켜지지 않는다
+        let together = |a: &Macro, b: &Macro| a.layer.is_empty() || b.layer.is_empty() || a.layer == b.layer;
+
+        let mut conflicts = Vec::new();
+        // 순서 트리거는 앞부분이 먼저 맞는 트리거가 실행된다. 같은 순서는 Duplicate로 보고한다
+        for (m, steps) in all.iter().filter(|(_, steps)| steps.len() > 1) {
+            let blocker = all.iter().find(|(by, other)| {
+                other.len() < steps.len() && steps.starts_with(other) && together(m, by)
+            });
+            if let Some((by, _)) = blocker {
+                conflicts.push(TriggerConflict::new(ConflictKind::AmbiguousSequence, &m.trigger, &[m, by]));
+            }
+        }
+
+        let enabled: Vec<(&Macro, Chord)> = all
+            .iter()
+            .filter(|(_, steps)| steps.len() == 1)
+            .map(|(m, steps)| (*m, steps[0].clone()))
+            .collect();
+        for (i, (m, chord)) in enabled.iter().enumerate() {
+            if toggle.as_ref().is_some_and(|toggle| toggle.matches(chord)) {
+                conflicts.push(TriggerConflict::new(ConflictKind::ToggleKey, &m.trigger, &[m]));
+                continue;
+            }
+            // 같은 키라도 누르는 방식이 다르면 (tap과 long_press) 함께 쓸 수 있다. 레이어 매크로는 기본 매크로를 가린다
+            let same = |other_m: &Macro, other: &Chord| other == chord && other_m.gesture == m.gesture && other_m.layer == m.layer;
+            if let Some((first, _)) = enabled[..i].iter().find(|(other_m, other)| same(other_m, other)) {
+                conflicts.push(TriggerConflict::new(ConflictKind::Duplicate, &m.trigger, &[m, first]));
+                continue;
+            }
+            // 조합의 수정 키를 마지막으로 누르는 순간 그 수정 키 트리거가 실행되고 키가 소비된다
+            let shadows = |other: &Chord| {
+                trigger::modifier_bit(&other.key).is_some_and(|bit| {
+                    chord.modifiers & bit != 0 && other.matches(&Chord::new(chord.modifiers & !bit, other.key.as_str()))
+                })
+            };
+            if toggle.as_ref().is_some_and(shadows) {
+                let mut conflict = TriggerConflict::new(ConflictKind::Shadowed, &m.trigger, &[m]);
+                conflict.names.push("토글 키".to_string());
+                conflicts.push(conflict);
+            } else if let Some((by, _)) = enabled.iter().find(|(by, other)| shadows(other) && together(m, by)) {
+                conflicts.push(TriggerConflict::new(ConflictKind::Shadowed, &m.trigger, &[m, by]));
+            }
+            // 주입한 키는 체인이 켜져 있을 때만 트리거가 된다. 끝난 뒤 보내는 트리거 키도 마찬가지
+            if self.chain_triggers {
+                let bound: HashSet<String> =
+                    enabled.iter().filter(|(by, _)| together(m, by)).map(|(_, other)| other.to_string()).collect();
+                if m.passthrough == Passthrough::After || self.presses_chord(m, chord, &bound) {
+                    conflicts.push(TriggerConflict::new(ConflictKind::SelfTrigger, &m.trigger, &[m]));
+                }
+            }
+        }
+        for (i, (m, steps)) in all.iter().enumerate().filter(|(_, (_, steps))| steps.len() > 1) {
+            if let Some((first, _)) = all[..i].iter().find(|(first, other)| other == steps && first.layer == m.layer) {
+                conflicts.push(TriggerConflict::new(ConflictKind::Duplicate, &m.trigger, &[m, first]));
+            }
+        }
+        conflicts
+    }
+
+    /// 펼친 액션 중 누른 수정 키와 함께 이 트리거를 실행하는 키가 있는지.
+    /// 수정 키 없이 적은 트리거도 수정 키를 누른 채로 실행되지만, `bound`에 그 조합이 있으면 그쪽이 실행된다
+    fn presses_chord(&self, m: &Macro, chord: &Chord, bound: &HashSet<String>) -> bool {
+        let fires = |pressed: &Chord| pressed.names().into_iter().find(|name| bound.contains(name)) == Some(chord.to_string());
+        let Ok(expanded) = self.expand(m) else { return false };
+        let mut held = 0;
+        for action in &expanded.actions {
+            let bit = trigger::modifier_bit(&action.key).unwrap_or(0);
+            if action.press != Press::Up && fires(&Chord::new(held, action.key.as_str())) {
+                return true;
+            }
+            match action.press {
+                Press::Down => held |= bit,
+                Press::Up => held &= !bit,
+                Press::Tap => {}
+            }
+        }
+        false
+    }
+}
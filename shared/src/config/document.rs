@@ -0,0 +1,238 @@
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table, Value};
+
+/// 표 배열의 항목을 알아보는 키 (앞의 것부터). 지우거나 순서를 바꿔도 주석이 같은 항목에 남는다.
+/// 어느 키도 없는 표 (`[[macros.actions]]`)는 위치로 맞춘다
+const IDENTITY_KEYS: [&str; 3] = ["id", "trigger", "name"];
+
+/// 기존 문서에 새 내용을 덮어쓴다. 값이 같은 항목은 손대지 않고,
+/// 바뀐 값은 주석/공백(decor)을 유지한 채 교체하며, 새 항목만 끝에 추가한다.
+/// `base`는 기존 문서를 읽어 다시 직렬화한 것으로, 여기에 있던 키가 새 내용에서 빠졌을 때만 지운다
+/// (기본값이라 직렬화되지 않는 `enabled = true` 같은 손으로 쓴 키는 남는다)
+pub fn merge_document(existing: &mut DocumentMut, base: Option<&DocumentMut>, updated: &DocumentMut) {
+    merge_table(existing.as_table_mut(), base.map(|b| b.as_table()), updated.as_table());
+}
+
+/// `before`에서 `after`로 바뀌며 새로 생긴 키를 `base`에서 뺀다 (버전 변환이 더한 id 등)
+pub fn forget_added(base: &mut Table, before: &toml::Table, after: &toml::Table) {
+    for (key, value) in after {
+        match (before.get(key), value, base.get_mut(key)) {
+            (None, _, _) => {
+                base.remove(key);
+            }
+            (Some(toml::Value::Table(b)), toml::Value::Table(a), Some(Item::Table(t))) => forget_added(t, b, a),
+            (Some(toml::Value::Array(b)), toml::Value::Array(a), Some(Item::ArrayOfTables(tables))) => {
+                for ((b, a), t) in b.iter().zip(a).zip(tables.iter_mut()) {
+                    if let (Some(b), Some(a)) = (b.as_table(), a.as_table()) {
+                        forget_added(t, b, a);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn merge_table(dst: &mut Table, base: Option<&Table>, src: &Table) {
+    let removed: Vec<String> = dst
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !src.contains_key(key) && base.is_none_or(|b| b.contains_key(key)))
+        .collect();
+    for key in removed {
+        dst.remove(&key);
+    }
+
+    for (key, src_item) in src.iter() {
+        match dst.get_mut(key) {
+            Some(dst_item) => {
+                // `actions = []` → `[[macros.actions]]` 처럼 형태가 바뀌면 키 주변 공백도 새 형태를 따른다
+                let reshaped = std::mem::discriminant(dst_item) != std::mem::discriminant(src_item);
+                merge_item(dst_item, base.and_then(|b| b.get(key)), src_item);
+                if reshaped && let Some(mut dst_key) = dst.key_mut(key) {
+                    dst_key.leaf_decor_mut().clear();
+                }
+            }
+            None if defaulted(base.and_then(|b| b.get(key)), src_item) => {}
+            None => {
+                dst.insert(key, src_item.clone());
+            }
+        }
+    }
+}
+
+/// 문서에 없던 키가 읽을 때 채워진 값 그대로인지 (`mode = 0` 같은 기본값, 붙여 준 id).
+/// 이런 키는 새로 적지 않는다
+fn defaulted(base: Option<&Item>, src: &Item) -> bool {
+    matches!((base, src), (Some(Item::Value(b)), Item::Value(s)) if value_eq(b, s))
+}
+
+fn merge_item(dst: &mut Item, base: Option<&Item>, src: &Item) {
+    match (dst, src) {
+        (Item::Table(d), Item::Table(s)) => merge_table(d, base.and_then(Item::as_table), s),
+        (Item::ArrayOfTables(d), Item::ArrayOfTables(s)) => {
+            merge_array_of_tables(d, base.and_then(Item::as_array_of_tables), s)
+        }
+        (Item::Value(d), Item::Value(s)) => merge_value(d, base.and_then(Item::as_value), s),
+        (dst, src) => *dst = src.clone(),
+    }
+}
+
+/// 새 내용의 순서로 다시 쌓는다. 같은 항목은 합치고, 없어진 항목은 빠지며, 새 항목은 그대로 들어간다
+fn merge_array_of_tables(dst: &mut ArrayOfTables, base: Option<&ArrayOfTables>, src: &ArrayOfTables) {
+    let mut slots = Vec::new();
+    for table in dst.iter() {
+        table_positions(table, &mut slots);
+    }
+    slots.sort_unstable();
+
+    let mut old: Vec<Option<Table>> = std::mem::take(dst).into_iter().map(Some).collect();
+    let mut merged = ArrayOfTables::new();
+    let base: Vec<Option<&Table>> = base.iter().flat_map(|b| b.iter()).map(Some).collect();
+    // base는 기존 문서를 읽은 것이라 항목이 같은 순서로 있다. 읽을 때 붙은 id도 있으므로
+    // id를 적지 않은 항목도 base를 거쳐 찾는다
+    let aligned = base.len() == old.len();
+    let mut reordered = false;
+    let mut last = None;
+    for (i, src_table) in src.iter().enumerate() {
+        let remaining: Vec<Option<&Table>> = old.iter().map(Option::as_ref).collect();
+        let remaining_base: Vec<Option<&Table>> =
+            base.iter().zip(&old).map(|(b, o)| b.filter(|_| aligned && o.is_some())).collect();
+        match find_table(&remaining_base, src_table, i).or_else(|| find_table(&remaining, src_table, i)) {
+            Some(j) => {
+                let mut table = old[j].take().expect("한 번만 맞춘다");
+                let base_table = if aligned { base[j] } else { find_table(&base, src_table, j).and_then(|k| base[k]) };
+                merge_table(&mut table, base_table, src_table);
+                reordered |= last.is_some_and(|last| j < last);
+                last = Some(j);
+                merged.push(table);
+            }
+            None => merged.push(src_table.clone()),
+        }
+    }
+
+    // 문서는 표 위치 순으로 쓰이므로 순서가 바뀌었으면 원래 자리를 새 순서대로 다시 나눠 준다
+    if reordered && let Some(&end) = slots.last() {
+        let mut slots = slots.into_iter();
+        for table in merged.iter_mut() {
+            renumber(table, &mut slots, end);
+        }
+    }
+    *dst = merged;
+}
+
+/// `src`와 같은 항목의 인덱스. 앞선 식별 키가 서로 다르면 뒤의 키로 맞추지 않는다
+fn find_table(tables: &[Option<&Table>], src: &Table, index: usize) -> Option<usize> {
+    let text = |table: &Table, key: &str| table.get(key).and_then(Item::as_str).map(str::to_string);
+    for (n, key) in IDENTITY_KEYS.iter().enumerate() {
+        let Some(value) = text(src, key) else {
+            continue;
+        };
+        let found = tables.iter().position(|table| {
+            table.is_some_and(|t| {
+                text(t, key).as_ref() == Some(&value)
+                    && IDENTITY_KEYS[..n].iter().all(|k| text(t, k).is_none() || text(src, k).is_none())
+            })
+        });
+        if found.is_some() {
+            return found;
+        }
+    }
+    let keyed = |t: &Table| IDENTITY_KEYS.iter().any(|key| t.contains_key(key));
+    match tables.get(index) {
+        Some(Some(table)) if !keyed(src) && !keyed(table) => Some(index),
+        _ => None,
+    }
+}
+
+/// 문서에 쓰이는 순서대로 표와 하위 표의 위치를 모은다
+fn table_positions(table: &Table, out: &mut Vec<usize>) {
+    if !table.is_dotted() {
+        out.extend(table.position());
+    }
+    for (_, item) in table.iter() {
+        match item {
+            Item::Table(t) => table_positions(t, out),
+            Item::ArrayOfTables(a) => a.iter().for_each(|t| table_positions(t, out)),
+            _ => {}
+        }
+    }
+}
+
+fn renumber(table: &mut Table, slots: &mut impl Iterator<Item = usize>, end: usize) {
+    if !table.is_dotted() {
+        table.set_position(slots.next().unwrap_or(end));
+    }
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Table(t) => renumber(t, slots, end),
+            Item::ArrayOfTables(a) => a.iter_mut().for_each(|t| renumber(t, slots, end)),
+            _ => {}
+        }
+    }
+}
+
+fn merge_value(dst: &mut Value, base: Option<&Value>, src: &Value) {
+    match (dst, src) {
+        (Value::InlineTable(d), Value::InlineTable(s)) => merge_inline_table(d, base.and_then(Value::as_inline_table), s),
+        (Value::Array(d), Value::Array(s)) => merge_array(d, s),
+        (dst, src) => {
+            if !value_eq(dst, src) {
+                let decor = dst.decor().clone();
+                *dst = src.clone();
+                *dst.decor_mut() = decor;
+            }
+        }
+    }
+}
+
+fn merge_inline_table(dst: &mut InlineTable, base: Option<&InlineTable>, src: &InlineTable) {
+    let removed: Vec<String> = dst
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !src.contains_key(key) && base.is_none_or(|b| b.contains_key(key)))
+        .collect();
+    for key in removed {
+        dst.remove(&key);
+    }
+
+    for (key, src_value) in src.iter() {
+        match dst.get_mut(key) {
+            Some(dst_value) => merge_value(dst_value, base.and_then(|b| b.get(key)), src_value),
+            None if base.and_then(|b| b.get(key)).is_some_and(|b| value_eq(b, src_value)) => {}
+            None => {
+                dst.insert(key, src_value.clone());
+            }
+        }
+    }
+}
+
+fn merge_array(dst: &mut Array, src: &Array) {
+    while dst.len() > src.len() {
+        dst.remove(dst.len() - 1);
+    }
+    for (i, src_value) in src.iter().enumerate() {
+        match dst.get_mut(i) {
+            Some(dst_value) => merge_value(dst_value, None, src_value),
+            None => dst.push(src_value.clone()),
+        }
+    }
+}
+
+/// 주석/공백을 무시한 값 비교
+fn value_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(x), Value::String(y)) => x.value() == y.value(),
+        (Value::Integer(x), Value::Integer(y)) => x.value() == y.value(),
+        (Value::Float(x), Value::Float(y)) => x.value() == y.value(),
+        (Value::Boolean(x), Value::Boolean(y)) => x.value() == y.value(),
+        (Value::Datetime(x), Value::Datetime(y)) => x.value() == y.value(),
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y.iter()).all(|(a, b)| value_eq(a, b))
+        }
+        (Value::InlineTable(x), Value::InlineTable(y)) => {
+            x.len() == y.len()
+                && x.iter().all(|(key, a)| y.get(key).is_some_and(|b| value_eq(a, b)))
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,193 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{is_generated_id, Macro, MacroConfig};
+use crate::trigger;
+
+/// 같은 트리거가 여러 곳에 정의된 경우
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IncludeConflict {
+    pub trigger: String,
+    /// 실제로 사용되는 매크로가 정의된 파일
+    pub kept: PathBuf,
+    /// 무시된 매크로가 정의된 파일
+    pub dropped: PathBuf,
+}
+
+/// include를 모두 펼친 설정
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    pub config: MacroConfig,
+    /// 읽은 파일 (병합 순서, 마지막이 최상위 파일)
+    pub files: Vec<PathBuf>,
+    pub conflicts: Vec<IncludeConflict>,
+}
+
+#[derive(Debug)]
+pub enum IncludeError {
+    Missing { path: PathBuf, from: PathBuf },
+    Cycle { chain: Vec<PathBuf> },
+    Invalid { path: PathBuf, message: String },
+    /// 서로 다른 파일에 적힌 같은 매크로 id
+    DuplicateId { id: String, first: PathBuf, second: PathBuf },
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncludeError::Missing { path, from } => write!(
+                f,
+                "포함 파일을 찾을 수 없습니다: {} ({}에서 include)",
+                path.display(),
+                from.display()
+            ),
+            IncludeError::Cycle { chain } => {
+                let chain: Vec<String> = chain.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "include가 순환합니다: {}", chain.join(" → "))
+            }
+            IncludeError::Invalid { path, message } => {
+                write!(f, "포함 파일 읽기 실패 ({}): {}", path.display(), message)
+            }
+            IncludeError::DuplicateId { id, first, second } => write!(
+                f,
+                "매크로 id가 겹칩니다: {} ({}, {})",
+                id,
+                first.display(),
+                second.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IncludeError {}
+
+/// 병합 규칙:
+/// - include 목록 순서대로, 각 파일의 include가 먼저 (깊이 우선)
+/// - 나중 파일이 앞 파일의 같은 트리거를 덮어쓴다 (조합키 표기 순서는 상관없다) (개인 설정 > 공용 라이브러리). 레이어가 다르면 따로 둔다
+/// - 한 파일 안의 중복 트리거는 먼저 나온 것이 남는다
+/// - toggle_key 등 매크로 외 설정은 최상위 파일의 값을 쓴다
+/// - 같은 파일을 여러 번 include 해도 한 번만 읽는다
+/// - 포함 파일에 id를 적지 않은 매크로는 병합한 뒤 겹치지 않는 id를 받는다. 예전 변환이 적은 id (macro-N)가
+///   겹쳐도 새 id를 받고, 직접 적은 id가 겹치면 오류
+pub fn resolve(path: &Path, root: MacroConfig) -> Result<ResolvedConfig, IncludeError> {
+    let root_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    let mut layers = Vec::new();
+    let mut stack = vec![root_path.clone()];
+    collect(&root_path, &root, &mut stack, &mut layers)?;
+    layers.push((root_path, root.macros.clone()));
+
+    let mut macros: Vec<(Macro, usize)> = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for (layer, (file, layer_macros)) in layers.iter().enumerate() {
+        for m in layer_macros {
+            let key = trigger::normalize(&m.trigger);
+            let same = |existing: &Macro| trigger::normalize(&existing.trigger) == key && existing.layer == m.layer;
+            match macros.iter().position(|(existing, _)| same(existing)) {
+                Some(i) if macros[i].1 == layer => conflicts.push(IncludeConflict {
+                    trigger: m.trigger.clone(),
+                    kept: file.clone(),
+                    dropped: file.clone(),
+                }),
+                Some(i) => {
+                    conflicts.push(IncludeConflict {
+                        trigger: m.trigger.clone(),
+                        kept: file.clone(),
+                        dropped: layers[macros[i].1].0.clone(),
+                    });
+                    macros[i] = (m.clone(), layer);
+                }
+                None => macros.push((m.clone(), layer)),
+            }
+        }
+    }
+
+    // 최상위 파일에 가까운 쪽이 id를 지킨다. 읽을 때 붙은 형식 (macro-N)이 겹치면 새 id를 받는다
+    let mut order: Vec<usize> = (0..macros.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(macros[i].1));
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    for i in order {
+        let (m, layer) = &mut macros[i];
+        if m.id.is_empty() {
+            continue;
+        }
+        match ids.get(&m.id) {
+            Some(_) if is_generated_id(&m.id) => m.id.clear(),
+            Some(&kept) => {
+                return Err(IncludeError::DuplicateId {
+                    id: m.id.clone(),
+                    first: layers[*layer].0.clone(),
+                    second: layers[kept].0.clone(),
+                });
+            }
+            None => {
+                ids.insert(m.id.clone(), *layer);
+            }
+        }
+    }
+
+    let mut config = MacroConfig {
+        macros: macros.into_iter().map(|(m, _)| m).collect(),
+        ..root
+    };
+    config.assign_missing_ids();
+    Ok(ResolvedConfig {
+        config,
+        files: layers.into_iter().map(|(file, _)| file).collect(),
+        conflicts,
+    })
+}
+
+fn collect(
+    path: &Path,
+    config: &MacroConfig,
+    stack: &mut Vec<PathBuf>,
+    layers: &mut Vec<(PathBuf, Vec<Macro>)>,
+) -> Result<(), IncludeError> {
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    for include in &config.include {
+        let target = base.join(include);
+        let target = fs::canonicalize(&target).map_err(|_| IncludeError::Missing {
+            path: target.clone(),
+            from: path.to_path_buf(),
+        })?;
+
+        if stack.contains(&target) {
+            let mut chain = stack.clone();
+            chain.push(target);
+            return Err(IncludeError::Cycle { chain });
+        }
+        if layers.iter().any(|(file, _)| *file == target) {
+            continue;
+        }
+
+        let invalid = |message: String| IncludeError::Invalid { path: target.clone(), message };
+        let content = fs::read_to_string(&target).map_err(|e| invalid(e.to_string()))?;
+        let mut included = MacroConfig::parse(&content).map_err(|e| invalid(e.to_string()))?;
+        forget_generated_ids(&content, &mut included);
+
+        stack.push(target.clone());
+        collect(&target, &included, stack, layers)?;
+        stack.pop();
+        layers.push((target, included.macros));
+    }
+
+    Ok(())
+}
+
+/// 파일에 적히지 않고 읽을 때 붙은 id (macro-N)를 지운다. 파일마다 따로 붙어 서로 겹치기 때문이다
+fn forget_generated_ids(content: &str, config: &mut MacroConfig) {
+    let raw: toml::Table = content.parse().unwrap_or_default();
+    let written = raw.get("macros").and_then(|m| m.as_array());
+    for (i, m) in config.macros.iter_mut().enumerate() {
+        let id = written.and_then(|w| w.get(i)).and_then(|w| w.get("id")).and_then(|id| id.as_str());
+        if id != Some(m.id.as_str()) {
+            m.id.clear();
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use serde::Serialize;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// 설정 파일 경로를 지정하는 환경 변수
+pub const CONFIG_ENV: &str = "KEYM_CONFIG";
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// 설정 파일을 어디서 찾았는지
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// 명령행 옵션
+    Flag,
+    /// KEYM_CONFIG 환경 변수
+    Env,
+    /// 실행 파일과 같은 폴더
+    ExeDir,
+    /// 사용자 설정 폴더 (Windows: %APPDATA%\KeyM, Linux: $XDG_CONFIG_HOME/keym)
+    UserDir,
+}
+
+/// 찾은 설정 파일 위치. 파일이 아직 없으면 새로 만들 위치를 가리킨다
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigLocation {
+    pub path: PathBuf,
+    pub source: ConfigSource,
+    pub exists: bool,
+}
+
+/// 설정 파일 찾기. 순서: 명령행 옵션 → 환경 변수 → 실행 파일 폴더 → 사용자 설정 폴더.
+/// 옵션과 환경 변수는 파일이 없어도 그대로 사용하고, 아무 파일도 없으면 사용자 설정 폴더를 쓴다
+pub fn locate(explicit: Option<&Path>) -> ConfigLocation {
+    let env_path = env::var_os(CONFIG_ENV).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let exe_dir = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+
+    locate_in(
+        explicit,
+        env_path.as_deref(),
+        exe_dir.as_deref(),
+        user_config_dir().as_deref(),
+        |p| p.is_file(),
+    )
+}
+
+/// 환경에 의존하지 않는 탐색 로직
+pub fn locate_in(
+    explicit: Option<&Path>,
+    env_path: Option<&Path>,
+    exe_dir: Option<&Path>,
+    user_dir: Option<&Path>,
+    exists: impl Fn(&Path) -> bool,
+) -> ConfigLocation {
+    let found = |path: PathBuf, source| ConfigLocation {
+        exists: exists(&path),
+        path,
+        source,
+    };
+
+    if let Some(path) = explicit {
+        return found(path.to_path_buf(), ConfigSource::Flag);
+    }
+    if let Some(path) = env_path {
+        return found(path.to_path_buf(), ConfigSource::Env);
+    }
+
+    let exe_candidate = exe_dir.map(|dir| found(dir.join(CONFIG_FILE_NAME), ConfigSource::ExeDir));
+    let user_candidate = user_dir.map(|dir| found(dir.join(CONFIG_FILE_NAME), ConfigSource::UserDir));
+
+    [&exe_candidate, &user_candidate]
+        .into_iter()
+        .flatten()
+        .find(|c| c.exists)
+        .or(user_candidate.as_ref())
+        .or(exe_candidate.as_ref())
+        .cloned()
+        .unwrap_or_else(|| found(PathBuf::from(CONFIG_FILE_NAME), ConfigSource::ExeDir))
+}
+
+/// 플랫폼별 사용자 설정 폴더
+pub fn user_config_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    {
+        env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("KeyM"))
+    }
+    #[cfg(not(windows))]
+    {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("keym"))
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+use super::new_macro_id;
+
+/// 현재 설정 파일 스키마 버전
+pub const CONFIG_VERSION: u32 = 2;
+
+/// 버전 N → N+1 변환. 인덱스가 곧 시작 버전
+type Migration = fn(&mut Table) -> Result<(), String>;
+
+const MIGRATIONS: &[Migration] = &[
+    // 0 → 1: 버전 키 도입 (구조 변화 없음)
+    |_| Ok(()),
+    // 1 → 2: 매크로마다 고정 id 부여 (Editor가 인덱스 대신 id로 찾는다).
+    // 새 매크로와 같은 방식으로 만들어 다른 파일의 매크로와 겹치지 않는다
+    add_macro_ids,
+];
+
+fn add_macro_ids(table: &mut Table) -> Result<(), String> {
+    let Some(macros) = table.get_mut("macros") else {
+        return Ok(());
+    };
+    let macros = macros.as_array_mut().ok_or("macros가 배열이 아닙니다")?;
+    for item in macros.iter_mut() {
+        let item = item.as_table_mut().ok_or("매크로 항목이 테이블이 아닙니다")?;
+        item.entry("id").or_insert_with(|| Value::String(new_macro_id()));
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum MigrateError {
+    /// 이 프로그램보다 새 버전에서 저장된 설정
+    TooNew { found: u32, supported: u32 },
+    InvalidVersion(String),
+    Failed { from: u32, message: String },
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrateError::TooNew { found, supported } => write!(
+                f,
+                "설정 파일 버전 {}은(는) 지원하지 않습니다 (지원: {} 이하). 프로그램을 업데이트하세요",
+                found, supported
+            ),
+            MigrateError::InvalidVersion(v) => write!(f, "잘못된 설정 파일 버전: {}", v),
+            MigrateError::Failed { from, message } => {
+                write!(f, "설정 파일 변환 실패 (버전 {} → {}): {}", from, from + 1, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrateError {}
+
+/// 문서의 버전 (버전 키가 없으면 0)
+pub fn document_version(table: &Table) -> Result<u32, MigrateError> {
+    match table.get("version") {
+        None => Ok(0),
+        Some(Value::Integer(v)) => u32::try_from(*v).map_err(|_| MigrateError::InvalidVersion(v.to_string())),
+        Some(other) => Err(MigrateError::InvalidVersion(other.to_string())),
+    }
+}
+
+/// 문서를 현재 버전까지 순서대로 변환하고, 원래 버전을 돌려준다
+pub fn upgrade(table: &mut Table) -> Result<u32, MigrateError> {
+    let found = document_version(table)?;
+    if found > CONFIG_VERSION {
+        return Err(MigrateError::TooNew { found, supported: CONFIG_VERSION });
+    }
+
+    for from in found..CONFIG_VERSION {
+        MIGRATIONS[from as usize](table).map_err(|message| MigrateError::Failed { from, message })?;
+        table.insert("version".to_string(), Value::Integer(i64::from(from + 1)));
+    }
+
+    Ok(found)
+}
+
+/// 변환 전 원본을 보관할 경로 (config.toml → config.toml.v0.bak)
+pub fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    path.with_file_name(name)
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// 매크로 매개변수 값. TOML에서는 숫자나 문자열로 쓴다
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Number(u64),
+    Text(String),
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Number(n) => write!(f, "{}", n),
+            ParamValue::Text(s) => f.write_str(s),
+        }
+    }
+}
+
+/// 이름 → 값
+pub type Scope = BTreeMap<String, ParamValue>;
+
+/// `$name`, `${name}`을 값으로 바꾼다. `$$`는 `$` 그대로.
+/// 없는 이름이 나오면 그 이름을 돌려준다
+pub fn substitute(input: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    if !input.contains('$') {
+        return Ok(input.to_string());
+    }
+
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        let (name, consumed) = if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+            continue;
+        } else if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        if name.is_empty() {
+            // 변수가 아닌 `$`는 글자 그대로
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        out.push_str(&lookup(name).ok_or_else(|| name.to_string())?);
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// scope에서 찾아 치환
+pub fn substitute_in(input: &str, scope: &Scope) -> Result<String, String> {
+    substitute(input, |name| scope.get(name).map(ToString::to_string))
+}
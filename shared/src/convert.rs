@@ -0,0 +1,4 @@
+//! 다른 자동화 도구의 스크립트와 서로 변환
+
+pub mod ahk;
+pub mod shell;
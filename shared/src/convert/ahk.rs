@@ -0,0 +1,715 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::config::{Macro, MacroAction, MacroConfig, ParamValue, Passthrough, Press};
+use crate::gesture::Gesture;
+use crate::trigger::{self, Chord};
+
+/// KeyM 키 이름 → AutoHotkey 키 이름. 글자, 숫자, 기호는 이름이 같다
+const KEY_NAMES: &[(&str, &str)] = &[
+    ("space", "Space"),
+    ("enter", "Enter"),
+    ("tab", "Tab"),
+    ("esc", "Escape"),
+    ("backspace", "Backspace"),
+    ("delete", "Delete"),
+    ("insert", "Insert"),
+    ("home", "Home"),
+    ("end", "End"),
+    ("pageup", "PgUp"),
+    ("pagedown", "PgDn"),
+    ("up", "Up"),
+    ("down", "Down"),
+    ("left", "Left"),
+    ("right", "Right"),
+    ("shift", "Shift"),
+    ("ctrl", "Ctrl"),
+    ("alt", "Alt"),
+    ("capslock", "CapsLock"),
+    ("numlock", "NumLock"),
+    ("scrolllock", "ScrollLock"),
+    ("f1", "F1"),
+    ("f2", "F2"),
+    ("f3", "F3"),
+    ("f4", "F4"),
+    ("f5", "F5"),
+    ("f6", "F6"),
+    ("f7", "F7"),
+    ("f8", "F8"),
+    ("f9", "F9"),
+    ("f10", "F10"),
+    ("f11", "F11"),
+    ("f12", "F12"),
+    ("num0", "Numpad0"),
+    ("num1", "Numpad1"),
+    ("num2", "Numpad2"),
+    ("num3", "Numpad3"),
+    ("num4", "Numpad4"),
+    ("num5", "Numpad5"),
+    ("num6", "Numpad6"),
+    ("num7", "Numpad7"),
+    ("num8", "Numpad8"),
+    ("num9", "Numpad9"),
+    ("num/", "NumpadDiv"),
+    ("num*", "NumpadMult"),
+    ("num-", "NumpadSub"),
+    ("num+", "NumpadAdd"),
+    ("num.", "NumpadDot"),
+    ("numenter", "NumpadEnter"),
+];
+
+/// KeyM 키 이름의 AutoHotkey 이름
+pub fn key_name(key: &str) -> Option<&str> {
+    if let Some((_, ahk)) = KEY_NAMES.iter().find(|(name, _)| *name == key) {
+        return Some(ahk);
+    }
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_lowercase() || c.is_ascii_digit() || "-=[];'`\\,./".contains(c) => Some(key),
+        _ => None,
+    }
+}
+
+/// 핫키 이름. 조합키는 `^!+` 접두사, `;`와 `` ` ``는 이스케이프해야 한다
+fn hotkey(trigger: &str) -> Option<String> {
+    let chord = Chord::parse(trigger);
+    let name = match key_name(&chord.key)? {
+        name @ (";" | "`") => format!("`{}", name),
+        name => name.to_string(),
+    };
+    let prefix: String = [(trigger::CTRL, '^'), (trigger::ALT, '!'), (trigger::SHIFT, '+')]
+        .iter()
+        .filter(|(bit, _)| chord.modifiers & bit != 0)
+        .map(|&(_, c)| c)
+        .collect();
+    Some(prefix + &name)
+}
+
+/// 따옴표 문자열 안의 Send 키
+fn send_key(name: &str, state: &str) -> String {
+    let name = if name == "`" { "``" } else { name };
+    format!("{{{} {}}}", name, state)
+}
+
+/// 설정 전체를 AutoHotkey v2 스크립트로. call, repeat, 매개변수는 펼쳐서 내보낸다.
+/// 옮길 수 없는 매크로나 키는 주석으로 남긴다
+pub fn export(config: &MacroConfig) -> String {
+    let mut out = String::new();
+    out.push_str("; KeyM에서 내보낸 AutoHotkey v2 스크립트\n");
+    out.push_str("#Requires AutoHotkey v2.0\n");
+    out.push_str("#SingleInstance Force\n");
+
+    let mut used = HashSet::new();
+    let toggle_key = trigger::normalize(&config.toggle_key);
+    match hotkey(&config.toggle_key) {
+        Some(toggle) => {
+            used.insert(toggle_key.clone());
+            out.push_str("\n; 토글 키: 매크로 켜기/끄기\n");
+            out.push_str("#SuspendExempt\n");
+            let _ = writeln!(out, "${}::Suspend -1", toggle);
+            out.push_str("#SuspendExempt False\n");
+        }
+        None if config.toggle_key.is_empty() => {}
+        None => {
+            let _ = writeln!(out, "\n; 토글 키를 옮길 수 없음: {}", config.toggle_key);
+        }
+    }
+
+    for m in &config.macros {
+        out.push('\n');
+        let _ = writeln!(out, "; {}", m.display_name());
+        for line in m.description.lines() {
+            let _ = writeln!(out, "; {}", line);
+        }
+
+        if !m.enabled {
+            out.push_str("; 비활성 매크로는 내보내지 않음\n");
+            continue;
+        }
+        if !m.layer.is_empty() {
+            let _ = writeln!(out, "; 레이어 매크로는 옮길 수 없음: {}", m.layer);
+            continue;
+        }
+        let Some(trigger) = hotkey(&m.trigger) else {
+            let _ = writeln!(out, "; 트리거 키를 옮길 수 없음: {}", m.trigger);
+            continue;
+        };
+        let suffix = match m.gesture {
+            Gesture::Press => "",
+            Gesture::Release => " up",
+            other => {
+                let _ = writeln!(out, "; 누르는 방식을 옮길 수 없음: {:?}", other);
+                continue;
+            }
+        };
+        let normalized = trigger::normalize(&m.trigger);
+        if normalized == toggle_key {
+            out.push_str("; 토글 키와 같은 트리거는 내보내지 않음\n");
+            continue;
+        }
+        // Runner처럼 먼저 정의된 매크로만 쓴다 (AutoHotkey는 중복 핫키를 허용하지 않는다)
+        if !used.insert(normalized + suffix) {
+            let _ = writeln!(out, "; 트리거가 겹쳐 건너뜀: {}", m.trigger);
+            continue;
+        }
+        let expanded = match config.expand(m) {
+            Ok(expanded) => expanded,
+            Err(e) => {
+                let _ = writeln!(out, "; 펼치기 실패: {}", e);
+                continue;
+            }
+        };
+
+        // ~는 원래 키를 막지 않는다
+        let prefix = if m.passthrough == Passthrough::Before { "~$" } else { "$" };
+        let _ = writeln!(out, "{}{}{}:: {{", prefix, trigger, suffix);
+        for action in &expanded.actions {
+            write_action(&mut out, action);
+        }
+        if m.passthrough == Passthrough::After
+            && let Some(name) = key_name(&Chord::parse(&m.trigger).key)
+        {
+            let _ = writeln!(out, "    Send \"{{Blind}}{}{}\"", send_key(name, "down"), send_key(name, "up"));
+        }
+        out.push_str("}\n");
+    }
+
+    out
+}
+
+fn write_action(out: &mut String, action: &MacroAction) {
+    let Some(name) = key_name(&action.key) else {
+        let _ = writeln!(out, "    ; 알 수 없는 키 건너뜀: {}", action.key);
+        return;
+    };
+
+    match action.press {
+        Press::Tap if action.hold_ms == 0 => {
+            let _ = writeln!(out, "    Send \"{}{}\"", send_key(name, "down"), send_key(name, "up"));
+        }
+        Press::Tap => {
+            let _ = writeln!(out, "    Send \"{}\"", send_key(name, "down"));
+            let _ = writeln!(out, "    Sleep {}", action.hold_ms);
+            let _ = writeln!(out, "    Send \"{}\"", send_key(name, "up"));
+        }
+        Press::Down => {
+            let _ = writeln!(out, "    Send \"{}\"", send_key(name, "down"));
+        }
+        Press::Up => {
+            let _ = writeln!(out, "    Send \"{}\"", send_key(name, "up"));
+        }
+    }
+    if action.delay_ms > 0 {
+        let _ = writeln!(out, "    Sleep {}", action.delay_ms);
+    }
+}
+
+// ---- 가져오기 ----
+
+/// AutoHotkey 키 이름 → KeyM 키 이름 (대소문자 무시, 좌우 구분 키는 하나로)
+pub fn from_key_name(name: &str) -> Option<String> {
+    const ALIASES: &[(&str, &str)] = &[
+        ("esc", "esc"),
+        ("return", "enter"),
+        ("bs", "backspace"),
+        ("del", "delete"),
+        ("ins", "insert"),
+        ("control", "ctrl"),
+        ("lctrl", "ctrl"),
+        ("rctrl", "ctrl"),
+        ("lshift", "shift"),
+        ("rshift", "shift"),
+        ("lalt", "alt"),
+        ("ralt", "alt"),
+    ];
+
+    let lower = name.to_ascii_lowercase();
+    if let Some((_, key)) = ALIASES.iter().find(|(alias, _)| *alias == lower) {
+        return Some(key.to_string());
+    }
+    if let Some((key, _)) = KEY_NAMES.iter().find(|(_, ahk)| ahk.eq_ignore_ascii_case(name)) {
+        return Some(key.to_string());
+    }
+    key_name(&lower).map(str::to_string)
+}
+
+/// 옮기지 못한 줄
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnsupportedLine {
+    /// 1부터
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+/// 가져오기 결과. 지원하지 않는 줄은 건너뛰고 나머지는 그대로 가져온다
+#[derive(Debug, Clone, Default)]
+pub struct AhkImport {
+    pub macros: Vec<Macro>,
+    /// `키::Suspend` 핫키
+    pub toggle_key: Option<String>,
+    pub unsupported: Vec<UnsupportedLine>,
+}
+
+/// 무시해도 동작이 같은 설정 줄
+const IGNORED: &[&str] = &[
+    "#requires",
+    "#singleinstance",
+    "#noenv",
+    "#persistent",
+    "#warn",
+    "#suspendexempt",
+    "#maxthreadsperhotkey",
+    "sendmode",
+    "setworkingdir",
+    "setkeydelay",
+];
+
+struct Frame {
+    actions: Vec<MacroAction>,
+    /// Loop 블록이면 반복 횟수
+    repeat: Option<u64>,
+}
+
+struct Hotkey {
+    trigger: String,
+    gesture: Gesture,
+    passthrough: Passthrough,
+    name: String,
+    line: usize,
+    /// false면 v1처럼 return으로 끝난다
+    braces: bool,
+    /// 다음 줄의 `{`를 기다리는 중
+    awaiting_open: bool,
+    frames: Vec<Frame>,
+    /// 트리거를 옮길 수 없어 본문을 읽기만 한다
+    skip: bool,
+}
+
+/// 지원하는 문법:
+/// - 핫키: `키::`, `^!+` 수정 키, `~` 접두사 (passthrough = "before"), `$ *` 접두사 (무시). 본문은 `{ }` 블록, v1의 `return`까지, 또는 같은 줄
+/// - `Send`/`SendInput`/`SendEvent`: 글자, `{키}`, `{키 down}`, `{키 up}`, `{키 N}`, `^!+` 수정 키
+/// - `Sleep N`, `Loop N { }`
+/// - `키::Suspend`는 토글 키가 된다
+///
+/// 그 밖의 줄은 줄 번호와 함께 unsupported로 보고하고 건너뛴다
+pub fn import(source: &str) -> AhkImport {
+    let mut result = AhkImport::default();
+    let mut current: Option<Hotkey> = None;
+    let mut comment: Option<String> = None;
+
+    for (index, raw) in source.lines().enumerate() {
+        let line_no = index + 1;
+        let trimmed = raw.trim();
+        if let Some(text) = trimmed.strip_prefix(';') {
+            comment = Some(text.trim().to_string());
+            continue;
+        }
+        let line = strip_comment(trimmed);
+        if line.is_empty() {
+            comment = None;
+            continue;
+        }
+        let report = |list: &mut Vec<UnsupportedLine>, reason: &str| {
+            list.push(UnsupportedLine {
+                line: line_no,
+                text: raw.trim().to_string(),
+                reason: reason.to_string(),
+            })
+        };
+
+        // 핫키 본문
+        if let Some(hotkey) = current.as_mut() {
+            if hotkey.awaiting_open {
+                hotkey.awaiting_open = false;
+                if line == "{" {
+                    if hotkey.frames.len() == 1 {
+                        hotkey.braces = true;
+                    }
+                    continue;
+                }
+                if hotkey.frames.len() > 1 {
+                    hotkey.frames.pop();
+                    if !hotkey.skip {
+                        report(&mut result.unsupported, "Loop 뒤에는 { } 블록이 필요합니다");
+                    }
+                }
+            }
+
+            if line == "}" {
+                let frame = hotkey.frames.pop().expect("핫키 프레임");
+                match (frame.repeat, hotkey.frames.last_mut()) {
+                    (Some(count), Some(parent)) => {
+                        if count > 0 && !frame.actions.is_empty() {
+                            parent.actions.push(MacroAction {
+                                repeat: Some(ParamValue::Number(count)),
+                                ..MacroAction::group(frame.actions, 0)
+                            });
+                        }
+                    }
+                    _ => {
+                        hotkey.frames.push(frame);
+                        if let Some(done) = current.take() {
+                            finish(done, &mut result);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            if line.eq_ignore_ascii_case("return") {
+                if !hotkey.braces && hotkey.frames.len() == 1
+                    && let Some(done) = current.take()
+                {
+                    finish(done, &mut result);
+                }
+                continue;
+            }
+
+            if let Some(count) = loop_count(line) {
+                // 옮길 수 없는 Loop도 블록은 읽어서 버린다 (0번 반복)
+                let count = count.unwrap_or_else(|reason| {
+                    if !hotkey.skip {
+                        report(&mut result.unsupported, &reason);
+                    }
+                    0
+                });
+                hotkey.awaiting_open = !line.ends_with('{');
+                hotkey.frames.push(Frame { actions: Vec::new(), repeat: Some(count) });
+                continue;
+            }
+
+            if split_hotkey(line).is_none() {
+                let skip = hotkey.skip;
+                let frame = hotkey.frames.last_mut().expect("핫키 프레임");
+                if let Err(reason) = statement(line, &mut frame.actions)
+                    && !skip
+                {
+                    report(&mut result.unsupported, &reason);
+                }
+                continue;
+            }
+
+            // v1에서 return 없이 다음 핫키가 시작됨
+            if hotkey.braces {
+                report(&mut result.unsupported, "이전 핫키 블록이 닫히지 않았습니다");
+            }
+            if let Some(done) = current.take() {
+                finish(done, &mut result);
+            }
+        }
+
+        // 핫키 정의
+        if let Some((lhs, rhs)) = split_hotkey(line) {
+            let name = comment.take().unwrap_or_default();
+            let parsed = match parse_hotkey(lhs) {
+                Ok(parsed) => Some(parsed),
+                Err(reason) => {
+                    report(&mut result.unsupported, &reason);
+                    None
+                }
+            };
+
+            if rhs.to_ascii_lowercase().starts_with("suspend") {
+                if let Some((trigger, _, _)) = parsed {
+                    result.toggle_key = Some(trigger);
+                }
+                continue;
+            }
+
+            let (trigger, gesture, passthrough) = parsed.clone().unwrap_or_default();
+            let mut hotkey = Hotkey {
+                skip: parsed.is_none(),
+                trigger,
+                gesture,
+                passthrough,
+                name,
+                line: line_no,
+                braces: rhs == "{",
+                awaiting_open: rhs.is_empty(),
+                frames: vec![Frame { actions: Vec::new(), repeat: None }],
+            };
+            if rhs.is_empty() || rhs == "{" {
+                current = Some(hotkey);
+            } else {
+                // 한 줄 핫키
+                if let Err(reason) = statement(rhs, &mut hotkey.frames[0].actions)
+                    && !hotkey.skip
+                {
+                    report(&mut result.unsupported, &reason);
+                }
+                finish(hotkey, &mut result);
+            }
+            continue;
+        }
+
+        comment = None;
+        let word = line.split([' ', ',', '\t']).next().unwrap_or_default().to_ascii_lowercase();
+        if !IGNORED.contains(&word.as_str()) {
+            report(&mut result.unsupported, "핫키 밖의 명령은 지원하지 않습니다");
+        }
+    }
+
+    if let Some(hotkey) = current {
+        if hotkey.braces || hotkey.frames.len() > 1 {
+            if !hotkey.skip {
+                result.unsupported.push(UnsupportedLine {
+                    line: hotkey.line,
+                    text: format!("{}::", hotkey.trigger),
+                    reason: "블록이 닫히지 않았습니다".to_string(),
+                });
+            }
+        } else {
+            finish(hotkey, &mut result);
+        }
+    }
+
+    result
+}
+
+fn finish(hotkey: Hotkey, result: &mut AhkImport) {
+    if hotkey.skip {
+        return;
+    }
+    let mut frames = hotkey.frames;
+    let mut actions = frames.swap_remove(0).actions;
+    merge_holds(&mut actions);
+    result.macros.push(Macro {
+        name: hotkey.name,
+        actions,
+        mode: 2,
+        gesture: hotkey.gesture,
+        passthrough: hotkey.passthrough,
+        ..Macro::new(hotkey.trigger)
+    });
+}
+
+/// `{a down}`, `Sleep 30`, `{a up}` → hold_ms 30인 tap 하나
+fn merge_holds(actions: &mut Vec<MacroAction>) {
+    let mut i = 0;
+    while i < actions.len() {
+        merge_holds(&mut actions[i].group);
+        if i + 1 < actions.len() {
+            let (down, up) = (&actions[i], &actions[i + 1]);
+            let plain = |a: &MacroAction| a.repeat.is_none() && a.group.is_empty() && a.call.is_none();
+            if down.press == Press::Down && up.press == Press::Up && down.key == up.key && plain(down) && plain(up) {
+                let tap = MacroAction::new(down.key.clone(), down.delay_ms, up.delay_ms);
+                actions.splice(i..i + 2, [tap]);
+            }
+        }
+        i += 1;
+    }
+}
+
+/// 문자열 밖의 ` ;` 뒤는 주석
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ';' if !in_string && prev.is_whitespace() => return line[..i].trim_end(),
+            _ => {}
+        }
+        prev = c;
+    }
+    line
+}
+
+/// `키::본문`
+fn split_hotkey(line: &str) -> Option<(&str, &str)> {
+    // "::"가 맨 앞이면 핫스트링
+    let pos = line.find("::").filter(|&pos| pos > 0)?;
+    Some((&line[..pos], line[pos + 2..].trim()))
+}
+
+/// 핫키 왼쪽 → 트리거와 누르는 방식 ("^1" → "ctrl+1", "F5 up" → 뗄 때)
+fn parse_hotkey(lhs: &str) -> Result<(String, Gesture, Passthrough), String> {
+    let mut rest = lhs.trim();
+    if rest.contains(" & ") {
+        return Err("두 키를 조합한 핫키(&)는 지원하지 않습니다".to_string());
+    }
+    let mut gesture = Gesture::Press;
+    if rest.to_ascii_lowercase().ends_with(" up") {
+        rest = rest[..rest.len() - 3].trim_end();
+        gesture = Gesture::Release;
+    }
+
+    let mut modifiers = 0;
+    let mut passthrough = Passthrough::Off;
+    while rest.chars().count() > 1 {
+        let c = rest.chars().next().unwrap_or_default();
+        match c {
+            '~' => passthrough = Passthrough::Before,
+            '$' | '*' => {}
+            '^' => modifiers |= trigger::CTRL,
+            '!' => modifiers |= trigger::ALT,
+            '+' => modifiers |= trigger::SHIFT,
+            '#' => return Err("Win 키 조합은 지원하지 않습니다".to_string()),
+            '<' | '>' => {}
+            _ => break,
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    let name = rest.strip_prefix('`').filter(|r| !r.is_empty()).unwrap_or(rest);
+    let key = from_key_name(name).ok_or_else(|| format!("알 수 없는 키: {}", name))?;
+    Ok((Chord::new(modifiers, key).to_string(), gesture, passthrough))
+}
+
+/// `Loop N` / `Loop, N {`. Loop 줄이 아니면 None
+fn loop_count(line: &str) -> Option<Result<u64, String>> {
+    let lower = line.to_ascii_lowercase();
+    let rest = lower.strip_prefix("loop")?;
+    if !rest.is_empty() && !rest.starts_with([' ', ',', '{', '\t']) {
+        return None;
+    }
+    let count = rest.trim_start_matches([' ', ',', '\t']).trim_end_matches('{').trim();
+    Some(
+        count
+            .parse()
+            .map_err(|_| format!("반복 횟수는 숫자여야 합니다 (무한 반복 불가): {}", count)),
+    )
+}
+
+/// 핫키 본문의 한 줄
+fn statement(line: &str, actions: &mut Vec<MacroAction>) -> Result<(), String> {
+    let (command, arg) = match line.find([' ', ',', '\t', '(']) {
+        Some(pos) => (&line[..pos], line[pos..].trim_start_matches([' ', ',', '\t']).trim()),
+        None => (line, ""),
+    };
+
+    match command.to_ascii_lowercase().as_str() {
+        "send" | "sendinput" | "sendevent" => {
+            let text = send_argument(arg)?;
+            actions.extend(send_keys(&text)?);
+            Ok(())
+        }
+        "sleep" => {
+            let arg = arg.trim_start_matches('(').trim_end_matches(')').trim();
+            let ms: u64 = arg.parse().map_err(|_| format!("Sleep 시간은 숫자여야 합니다: {}", arg))?;
+            let last = actions.last_mut().ok_or("동작 앞의 Sleep은 지원하지 않습니다")?;
+            last.append_delay(ms);
+            Ok(())
+        }
+        _ => Err(format!("지원하지 않는 명령: {}", command)),
+    }
+}
+
+/// v2 `"..."` 문자열 또는 v1 원문
+fn send_argument(arg: &str) -> Result<String, String> {
+    let arg = arg.strip_prefix('(').and_then(|a| a.strip_suffix(')')).unwrap_or(arg).trim();
+    let Some(inner) = arg.strip_prefix('"') else {
+        return Ok(arg.to_string());
+    };
+    let inner = inner.strip_suffix('"').ok_or("따옴표가 닫히지 않았습니다")?;
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '`' {
+            match chars.next() {
+                Some('n') => text.push_str("{Enter}"),
+                Some('t') => text.push_str("{Tab}"),
+                Some(escaped) => text.push(escaped),
+                None => text.push('`'),
+            }
+        } else {
+            text.push(c);
+        }
+    }
+    Ok(text)
+}
+
+/// Send 문자열 → 액션
+fn send_keys(text: &str) -> Result<Vec<MacroAction>, String> {
+    let mut actions = Vec::new();
+    let mut modifiers: Vec<&str> = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let (key, state) = match c {
+            '^' => {
+                modifiers.push("ctrl");
+                continue;
+            }
+            '!' => {
+                modifiers.push("alt");
+                continue;
+            }
+            '+' => {
+                modifiers.push("shift");
+                continue;
+            }
+            '#' => return Err("Win 키 조합은 지원하지 않습니다".to_string()),
+            '{' => {
+                let mut inner = String::new();
+                // {}} 처럼 닫는 괄호 자체를 보내는 경우
+                if chars.peek() == Some(&'}') {
+                    inner.push(chars.next().unwrap_or_default());
+                }
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => inner.push(c),
+                        None => return Err("{ 가 닫히지 않았습니다".to_string()),
+                    }
+                }
+                let mut parts = inner.split_whitespace();
+                let name = parts.next().ok_or("빈 {} 입니다")?;
+                // 누르고 있는 수정 키를 떼지 않는다는 뜻이라 따로 보낼 것이 없다 (내보내기가 쓴다)
+                if name.eq_ignore_ascii_case("blind") && parts.next().is_none() {
+                    continue;
+                }
+                let key = from_key_name(name).ok_or_else(|| format!("알 수 없는 키: {}", name))?;
+                let state = match parts.next().map(str::to_ascii_lowercase) {
+                    None => KeyState::Tap(1),
+                    Some(s) if s == "down" => KeyState::Down,
+                    Some(s) if s == "up" => KeyState::Up,
+                    Some(s) => KeyState::Tap(s.parse().map_err(|_| format!("알 수 없는 키 상태: {}", s))?),
+                };
+                (key, state)
+            }
+            ' ' => ("space".to_string(), KeyState::Tap(1)),
+            c if c.is_ascii_uppercase() => {
+                modifiers.push("shift");
+                (c.to_ascii_lowercase().to_string(), KeyState::Tap(1))
+            }
+            c => {
+                let key = key_name(&c.to_string())
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("보낼 수 없는 글자: {}", c))?;
+                (key, KeyState::Tap(1))
+            }
+        };
+
+        for modifier in &modifiers {
+            actions.push(MacroAction::press(*modifier, Press::Down, 0));
+        }
+        match state {
+            KeyState::Tap(0) => {}
+            KeyState::Tap(1) => actions.push(MacroAction::new(key, 0, 0)),
+            // {Enter 2}. 큰 횟수도 액션 하나로 둔다
+            KeyState::Tap(count) => actions.push(MacroAction {
+                repeat: Some(ParamValue::Number(count)),
+                ..MacroAction::new(key, 0, 0)
+            }),
+            KeyState::Down => actions.push(MacroAction::press(key, Press::Down, 0)),
+            KeyState::Up => actions.push(MacroAction::press(key, Press::Up, 0)),
+        }
+        for modifier in modifiers.drain(..).rev() {
+            actions.push(MacroAction::press(modifier, Press::Up, 0));
+        }
+    }
+
+    if !modifiers.is_empty() {
+        return Err("수정 키 뒤에 키가 없습니다".to_string());
+    }
+    Ok(actions)
+}
+
+enum KeyState {
+    Tap(u64),
+    Down,
+    Up,
+}
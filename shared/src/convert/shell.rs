@@ -0,0 +1,291 @@
+//! 리눅스용 셸 스크립트 내보내기. X11은 xdotool, Wayland는 ydotool
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::config::{ExpandError, Macro, MacroAction, MacroConfig, Press};
+use crate::scancode::SCANCODE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// X keysym 이름 사용
+    Xdotool,
+    /// 리눅스 입력 이벤트 코드 사용 (ydotoold가 떠 있어야 한다)
+    Ydotool,
+}
+
+impl Tool {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "xdotool" => Some(Tool::Xdotool),
+            "ydotool" => Some(Tool::Ydotool),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Xdotool => "xdotool",
+            Tool::Ydotool => "ydotool",
+        }
+    }
+
+    fn key(self, key: &str) -> Option<String> {
+        match self {
+            Tool::Xdotool => keysym(key).map(str::to_string),
+            Tool::Ydotool => evdev_code(key).map(|code| code.to_string()),
+        }
+    }
+}
+
+/// KeyM 키 이름 → X keysym. 글자와 숫자는 이름이 같다
+const KEYSYMS: &[(&str, &str)] = &[
+    ("space", "space"),
+    ("enter", "Return"),
+    ("tab", "Tab"),
+    ("esc", "Escape"),
+    ("backspace", "BackSpace"),
+    ("delete", "Delete"),
+    ("insert", "Insert"),
+    ("home", "Home"),
+    ("end", "End"),
+    ("pageup", "Page_Up"),
+    ("pagedown", "Page_Down"),
+    ("up", "Up"),
+    ("down", "Down"),
+    ("left", "Left"),
+    ("right", "Right"),
+    ("shift", "Shift_L"),
+    ("ctrl", "Control_L"),
+    ("alt", "Alt_L"),
+    ("capslock", "Caps_Lock"),
+    ("numlock", "Num_Lock"),
+    ("scrolllock", "Scroll_Lock"),
+    ("f1", "F1"),
+    ("f2", "F2"),
+    ("f3", "F3"),
+    ("f4", "F4"),
+    ("f5", "F5"),
+    ("f6", "F6"),
+    ("f7", "F7"),
+    ("f8", "F8"),
+    ("f9", "F9"),
+    ("f10", "F10"),
+    ("f11", "F11"),
+    ("f12", "F12"),
+    ("-", "minus"),
+    ("=", "equal"),
+    ("[", "bracketleft"),
+    ("]", "bracketright"),
+    (";", "semicolon"),
+    ("'", "apostrophe"),
+    ("`", "grave"),
+    ("\\", "backslash"),
+    (",", "comma"),
+    (".", "period"),
+    ("/", "slash"),
+    ("num0", "KP_0"),
+    ("num1", "KP_1"),
+    ("num2", "KP_2"),
+    ("num3", "KP_3"),
+    ("num4", "KP_4"),
+    ("num5", "KP_5"),
+    ("num6", "KP_6"),
+    ("num7", "KP_7"),
+    ("num8", "KP_8"),
+    ("num9", "KP_9"),
+    ("num/", "KP_Divide"),
+    ("num*", "KP_Multiply"),
+    ("num-", "KP_Subtract"),
+    ("num+", "KP_Add"),
+    ("num.", "KP_Decimal"),
+    ("numenter", "KP_Enter"),
+];
+
+/// 확장 키의 입력 이벤트 코드. 나머지 키는 스캔코드와 같다
+const EXTENDED_CODES: &[(&str, u16)] = &[
+    ("up", 103),
+    ("down", 108),
+    ("left", 105),
+    ("right", 106),
+    ("delete", 111),
+    ("insert", 110),
+    ("home", 102),
+    ("end", 107),
+    ("pageup", 104),
+    ("pagedown", 109),
+    ("num/", 98),
+    ("numenter", 96),
+];
+
+/// KeyM 키 이름의 X keysym
+pub fn keysym(key: &str) -> Option<&str> {
+    if let Some((_, sym)) = KEYSYMS.iter().find(|(name, _)| *name == key) {
+        return Some(sym);
+    }
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_lowercase() || c.is_ascii_digit() => Some(key),
+        _ => None,
+    }
+}
+
+/// KeyM 키 이름의 리눅스 입력 이벤트 코드 (KEY_*)
+pub fn evdev_code(key: &str) -> Option<u16> {
+    if SCANCODE.is_extended(key) {
+        return EXTENDED_CODES.iter().find(|(name, _)| *name == key).map(|&(_, code)| code);
+    }
+    SCANCODE.get(key)
+}
+
+/// 매크로 하나를 그대로 실행하는 스크립트
+pub fn export_macro(config: &MacroConfig, macro_item: &Macro, tool: Tool) -> Result<String, ExpandError> {
+    let expanded = config.expand(macro_item)?;
+    let mut out = header(tool, "");
+    let _ = writeln!(out, "\n# {}", macro_item.display_name());
+    for action in &expanded.actions {
+        write_action(&mut out, action, tool, "");
+    }
+    Ok(out)
+}
+
+/// 설정 전체를 함수 하나씩으로 내보낸다. 첫 인자(id 또는 트리거)로 실행할 매크로를 고른다
+pub fn export(config: &MacroConfig, tool: Tool) -> String {
+    let mut out = header(tool, "# 사용법: keym.sh <매크로 id 또는 트리거>\n");
+
+    let mut arms = Vec::new();
+    let mut used = HashSet::new();
+    let mut functions = HashSet::new();
+    for m in &config.macros {
+        out.push('\n');
+        let _ = writeln!(out, "# {}", m.display_name());
+        for line in m.description.lines() {
+            let _ = writeln!(out, "# {}", line);
+        }
+
+        if !m.enabled {
+            out.push_str("# 비활성 매크로는 내보내지 않음\n");
+            continue;
+        }
+        let expanded = match config.expand(m) {
+            Ok(expanded) => expanded,
+            Err(e) => {
+                let _ = writeln!(out, "# 펼치기 실패: {}", e);
+                continue;
+            }
+        };
+
+        let function = function_name(&m.id, &mut functions);
+        let _ = writeln!(out, "{}() {{", function);
+        let mut commands = 0;
+        for action in &expanded.actions {
+            commands += write_action(&mut out, action, tool, "    ") as usize;
+        }
+        // 명령이 없는 함수는 문법 오류
+        if commands == 0 {
+            out.push_str("    :\n");
+        }
+        out.push_str("}\n");
+
+        // 먼저 정의된 매크로가 이긴다 (Runner와 같다)
+        let patterns: Vec<String> = [m.id.as_str(), m.trigger.as_str()]
+            .into_iter()
+            .filter(|name| !name.is_empty() && used.insert(name.to_string()))
+            .map(quote)
+            .collect();
+        if !patterns.is_empty() {
+            arms.push(format!("    {}) {} ;;", patterns.join("|"), function));
+        }
+    }
+
+    out.push_str("\ncase \"${1:-}\" in\n");
+    for arm in arms {
+        out.push_str(&arm);
+        out.push('\n');
+    }
+    out.push_str("    *)\n");
+    out.push_str("        echo \"사용법: $0 <매크로 id 또는 트리거>\" >&2\n");
+    out.push_str("        exit 1\n");
+    out.push_str("        ;;\n");
+    out.push_str("esac\n");
+    out
+}
+
+fn header(tool: Tool, usage: &str) -> String {
+    let mut out = String::from("#!/bin/sh\n");
+    let _ = writeln!(out, "# KeyM에서 내보낸 {} 스크립트", tool.name());
+    if tool == Tool::Ydotool {
+        out.push_str("# ydotoold가 실행 중이어야 합니다\n");
+    }
+    out.push_str(usage);
+    out.push_str("set -e\n");
+    out
+}
+
+/// 명령을 썼으면 true (알 수 없는 키는 주석만 남긴다)
+fn write_action(out: &mut String, action: &MacroAction, tool: Tool, indent: &str) -> bool {
+    let Some(key) = tool.key(&action.key) else {
+        let _ = writeln!(out, "{}# 알 수 없는 키 건너뜀: {}", indent, action.key);
+        return false;
+    };
+
+    let (down, up) = match tool {
+        Tool::Xdotool => (format!("xdotool keydown {}", key), format!("xdotool keyup {}", key)),
+        Tool::Ydotool => (format!("ydotool key {}:1", key), format!("ydotool key {}:0", key)),
+    };
+    match action.press {
+        Press::Tap if action.hold_ms == 0 => match tool {
+            Tool::Xdotool => {
+                let _ = writeln!(out, "{}xdotool key {}", indent, key);
+            }
+            Tool::Ydotool => {
+                let _ = writeln!(out, "{}ydotool key {}:1 {}:0", indent, key, key);
+            }
+        },
+        Press::Tap => {
+            let _ = writeln!(out, "{}{}", indent, down);
+            let _ = writeln!(out, "{}sleep {}", indent, seconds(action.hold_ms));
+            let _ = writeln!(out, "{}{}", indent, up);
+        }
+        Press::Down => {
+            let _ = writeln!(out, "{}{}", indent, down);
+        }
+        Press::Up => {
+            let _ = writeln!(out, "{}{}", indent, up);
+        }
+    }
+    if action.delay_ms > 0 {
+        let _ = writeln!(out, "{}sleep {}", indent, seconds(action.delay_ms));
+    }
+    true
+}
+
+/// sleep 인자 (1500 → "1.5")
+fn seconds(ms: u64) -> String {
+    if ms.is_multiple_of(1000) {
+        return (ms / 1000).to_string();
+    }
+    let text = format!("{}.{:03}", ms / 1000, ms % 1000);
+    text.trim_end_matches('0').to_string()
+}
+
+/// 셸 함수 이름으로 쓸 수 없는 글자는 _로. 바꾼 이름이 겹치면 (macro-1, macro_1) 뒤에 번호를 붙인다
+fn function_name(id: &str, used: &mut HashSet<String>) -> String {
+    let body: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let base = format!("macro_{}", body);
+    let mut name = base.clone();
+    let mut n = 1;
+    while !used.insert(name.clone()) {
+        n += 1;
+        name = format!("{}_{}", base, n);
+    }
+    name
+}
+
+/// 작은따옴표 문자열
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
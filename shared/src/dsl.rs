@@ -0,0 +1,435 @@
+//! 손으로 쓰기 쉬운 매크로 문법
+//!
+//! ```text
+//! tap a 30ms; wait 100ms
+//! hold shift { tap 1 }
+//! repeat 5 { tap e; wait 50ms }
+//! call login user=$name
+//! ```
+//!
+//! 문장은 `;`나 줄바꿈으로 나누고 `#`부터 줄 끝까지는 주석이다.
+//! 공백이나 `;{}"#`가 들어간 키는 `";"`처럼 따옴표로 감싼다
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::config::{MacroAction, ParamValue, Press};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DslError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DslError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}줄 {}열: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for DslError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// 낱말. 따옴표로 감싼 부분이 있었으면 quoted
+    Word { text: String, quoted: bool },
+    Open,
+    Close,
+    Sep,
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Spanned {
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ';' | '{' | '}' | '"')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Spanned>, DslError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let (mut line, mut column) = (1, 1);
+
+    macro_rules! bump {
+        () => {{
+            let c = chars.next();
+            if c == Some('\n') {
+                line += 1;
+                column = 1;
+            } else if c.is_some() {
+                column += 1;
+            }
+            c
+        }};
+    }
+
+    while let Some(&c) = chars.peek() {
+        let (start_line, start_column) = (line, column);
+        let token = match c {
+            '\n' | ';' => {
+                bump!();
+                Token::Sep
+            }
+            c if c.is_whitespace() => {
+                bump!();
+                continue;
+            }
+            '#' => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    bump!();
+                }
+                continue;
+            }
+            '{' => {
+                bump!();
+                Token::Open
+            }
+            '}' => {
+                bump!();
+                Token::Close
+            }
+            _ => {
+                let mut text = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if c == '"' {
+                        quoted = true;
+                        bump!();
+                        loop {
+                            match bump!() {
+                                Some('"') => break,
+                                Some('\\') => match bump!() {
+                                    Some(escaped) => text.push(escaped),
+                                    None => break,
+                                },
+                                Some(c) => text.push(c),
+                                None => {
+                                    return Err(DslError {
+                                        line: start_line,
+                                        column: start_column,
+                                        message: "따옴표가 닫히지 않았습니다".to_string(),
+                                    })
+                                }
+                            }
+                        }
+                    } else if is_delimiter(c) {
+                        break;
+                    } else {
+                        text.push(c);
+                        bump!();
+                    }
+                }
+                Token::Word { text, quoted }
+            }
+        };
+        tokens.push(Spanned { token, line: start_line, column: start_column });
+    }
+
+    tokens.push(Spanned { token: Token::End, line, column });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Spanned {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Spanned {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error_at(at: &Spanned, message: impl Into<String>) -> DslError {
+        DslError { line: at.line, column: at.column, message: message.into() }
+    }
+
+    fn word(&mut self, what: &str) -> Result<(String, bool), DslError> {
+        let at = self.next();
+        match at.token {
+            Token::Word { text, quoted } if quoted || !text.is_empty() => Ok((text, quoted)),
+            _ => Err(Self::error_at(&at, format!("{}이(가) 필요합니다", what))),
+        }
+    }
+
+    fn duration(&mut self) -> Result<u64, DslError> {
+        let at = self.peek().clone();
+        let (text, _) = self.word("시간")?;
+        parse_duration(&text).ok_or_else(|| Self::error_at(&at, format!("잘못된 시간입니다: {} (예: 30ms, 1s)", text)))
+    }
+
+    /// 다음 낱말이 시간이면 읽는다
+    fn optional_duration(&mut self) -> Result<Option<u64>, DslError> {
+        match &self.peek().token {
+            Token::Word { text, quoted: false } if text.starts_with(|c: char| c.is_ascii_digit()) => {
+                self.duration().map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn expect(&mut self, token: Token, message: &str) -> Result<(), DslError> {
+        let at = self.next();
+        if at.token == token {
+            Ok(())
+        } else {
+            Err(Self::error_at(&at, message))
+        }
+    }
+
+    /// `}`나 입력 끝까지의 문장들
+    fn statements(&mut self, out: &mut Vec<MacroAction>) -> Result<(), DslError> {
+        loop {
+            while self.peek().token == Token::Sep {
+                self.next();
+            }
+            if matches!(self.peek().token, Token::Close | Token::End) {
+                return Ok(());
+            }
+
+            let ended_with_block = self.statement(out)?;
+            match self.peek().token {
+                Token::Sep | Token::Close | Token::End => {}
+                _ if ended_with_block => {}
+                _ => return Err(Self::error_at(self.peek(), "문장 끝에 ; 또는 줄바꿈이 필요합니다")),
+            }
+        }
+    }
+
+    /// 한 문장. 블록으로 끝났으면 true
+    fn statement(&mut self, out: &mut Vec<MacroAction>) -> Result<bool, DslError> {
+        let at = self.peek().clone();
+        let (keyword, _) = self.word("명령")?;
+
+        match keyword.as_str() {
+            "tap" => {
+                let (key, _) = self.word("키")?;
+                let hold_ms = self.optional_duration()?.unwrap_or(0);
+                out.push(MacroAction::new(key, hold_ms, 0));
+            }
+            "down" | "up" => {
+                let (key, _) = self.word("키")?;
+                let press = if keyword == "down" { Press::Down } else { Press::Up };
+                out.push(MacroAction::press(key, press, 0));
+            }
+            "wait" => {
+                let delay_ms = self.duration()?;
+                let last = out
+                    .last_mut()
+                    .ok_or_else(|| Self::error_at(&at, "wait 앞에 동작이 없습니다"))?;
+                last.append_delay(delay_ms);
+            }
+            "hold" => {
+                let (key, _) = self.word("키")?;
+                self.expect(Token::Open, "hold 뒤에 { 가 필요합니다")?;
+                out.push(MacroAction::press(key.clone(), Press::Down, 0));
+                self.statements(out)?;
+                self.expect(Token::Close, "} 가 필요합니다")?;
+                out.push(MacroAction::press(key, Press::Up, 0));
+                return Ok(true);
+            }
+            "repeat" => {
+                let (count, quoted) = self.word("반복 횟수")?;
+                let count = param_value(count, quoted);
+                if let ParamValue::Text(text) = &count
+                    && !text.starts_with('$')
+                {
+                    return Err(Self::error_at(&at, format!("반복 횟수는 숫자나 $매개변수여야 합니다: {}", text)));
+                }
+
+                self.expect(Token::Open, "repeat 뒤에 { 가 필요합니다")?;
+                let mut inner = Vec::new();
+                self.statements(&mut inner)?;
+                self.expect(Token::Close, "} 가 필요합니다")?;
+
+                match inner.len() {
+                    0 => return Err(Self::error_at(&at, "반복할 동작이 없습니다")),
+                    1 if inner[0].repeat.is_none() => {
+                        let mut action = inner.remove(0);
+                        action.repeat = Some(count);
+                        out.push(action);
+                    }
+                    _ => out.push(MacroAction {
+                        repeat: Some(count),
+                        ..MacroAction::group(inner, 0)
+                    }),
+                }
+                return Ok(true);
+            }
+            "call" => {
+                let (target, _) = self.word("호출할 매크로")?;
+                let mut args = BTreeMap::new();
+                while let Token::Word { text, quoted } = &self.peek().token {
+                    let Some((name, value)) = text.split_once('=').filter(|(name, _)| !name.is_empty()) else {
+                        return Err(Self::error_at(self.peek(), format!("인자는 이름=값 형식이어야 합니다: {}", text)));
+                    };
+                    args.insert(name.to_string(), param_value(value.to_string(), *quoted));
+                    self.next();
+                }
+                out.push(MacroAction { args, ..MacroAction::call(target, 0) });
+            }
+            other => return Err(Self::error_at(&at, format!("알 수 없는 명령입니다: {}", other))),
+        }
+        Ok(false)
+    }
+}
+
+fn param_value(text: String, quoted: bool) -> ParamValue {
+    match text.parse() {
+        Ok(n) if !quoted => ParamValue::Number(n),
+        _ => ParamValue::Text(text),
+    }
+}
+
+/// `30ms`, `2s`, 단위가 없으면 ms
+fn parse_duration(text: &str) -> Option<u64> {
+    if let Some(ms) = text.strip_suffix("ms") {
+        ms.parse().ok()
+    } else if let Some(s) = text.strip_suffix('s') {
+        s.parse::<u64>().ok()?.checked_mul(1000)
+    } else {
+        text.parse().ok()
+    }
+}
+
+/// 문법 → 액션 목록
+pub fn parse(input: &str) -> Result<Vec<MacroAction>, DslError> {
+    let mut parser = Parser { tokens: tokenize(input)?, pos: 0 };
+    let mut actions = Vec::new();
+    parser.statements(&mut actions)?;
+    if parser.peek().token != Token::End {
+        return Err(Parser::error_at(parser.peek(), "짝이 맞지 않는 } 입니다"));
+    }
+    Ok(actions)
+}
+
+/// 액션 목록 → 문법. parse로 다시 읽으면 같은 순서와 시간으로 실행된다
+pub fn print(actions: &[MacroAction]) -> String {
+    let mut out = Vec::new();
+    statements(actions, &mut out);
+    out.join("; ")
+}
+
+fn statements(actions: &[MacroAction], out: &mut Vec<String>) {
+    let mut i = 0;
+    while i < actions.len() {
+        let action = &actions[i];
+        if let Some(up) = matching_up(actions, i) {
+            let mut inner = Vec::new();
+            push_wait(&mut inner, action.delay_ms);
+            statements(&actions[i + 1..up], &mut inner);
+            out.push(block(&format!("hold {}", quote(&action.key)), &inner));
+            push_wait(out, actions[up].delay_ms);
+            i = up + 1;
+            continue;
+        }
+
+        let mut body = Vec::new();
+        if !action.group.is_empty() {
+            statements(&action.group, &mut body);
+        } else if let Some(target) = &action.call {
+            let mut call = format!("call {}", quote(target));
+            for (name, value) in &action.args {
+                let value = match value {
+                    ParamValue::Number(n) => n.to_string(),
+                    ParamValue::Text(text) if text.parse::<u64>().is_ok() => format!("\"{}\"", text),
+                    ParamValue::Text(text) => quote(text),
+                };
+                call.push_str(&format!(" {}={}", name, value));
+            }
+            body.push(call);
+        } else {
+            body.push(match action.press {
+                Press::Tap if action.hold_ms > 0 => {
+                    format!("tap {} {}", quote(&action.key), format_duration(action.hold_ms))
+                }
+                Press::Tap => format!("tap {}", quote(&action.key)),
+                Press::Down => format!("down {}", quote(&action.key)),
+                Press::Up => format!("up {}", quote(&action.key)),
+            });
+        }
+
+        match &action.repeat {
+            // 반복마다 쉬는 delay는 블록 안에 둔다
+            Some(count) => {
+                push_wait(&mut body, action.delay_ms);
+                out.push(block(&format!("repeat {}", count), &body));
+            }
+            None => {
+                out.extend(body);
+                push_wait(out, action.delay_ms);
+            }
+        }
+        i += 1;
+    }
+}
+
+/// actions[i]가 반복 없는 down이면 같은 키의 up 위치
+fn matching_up(actions: &[MacroAction], i: usize) -> Option<usize> {
+    let down = &actions[i];
+    let plain = |a: &MacroAction| a.repeat.is_none() && a.group.is_empty() && a.call.is_none();
+    if down.press != Press::Down || !plain(down) {
+        return None;
+    }
+    for (j, action) in actions.iter().enumerate().skip(i + 1) {
+        if action.key == down.key && plain(action) {
+            match action.press {
+                Press::Up => return Some(j),
+                Press::Down => return None,
+                Press::Tap => {}
+            }
+        }
+    }
+    None
+}
+
+fn block(head: &str, body: &[String]) -> String {
+    if body.is_empty() {
+        format!("{} {{}}", head)
+    } else {
+        format!("{} {{ {} }}", head, body.join("; "))
+    }
+}
+
+fn push_wait(out: &mut Vec<String>, delay_ms: u64) {
+    if delay_ms > 0 {
+        out.push(format!("wait {}", format_duration(delay_ms)));
+    }
+}
+
+fn format_duration(ms: u64) -> String {
+    if ms.is_multiple_of(1000) {
+        format!("{}s", ms / 1000)
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+/// 구분 문자가 들어 있으면 따옴표로 감싼다
+fn quote(text: &str) -> String {
+    if !text.is_empty() && !text.starts_with('#') && !text.contains(is_delimiter) {
+        return text.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,310 @@
+use serde::Serialize;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::config::Macro;
+use crate::input::InputSystem;
+use crate::scancode::SCANCODE;
+use crate::timeline::{KeyOp, Timeline};
+
+/// 취소 플래그 확인 간격
+const CANCEL_SLICE: Duration = Duration::from_millis(10);
+
+/// 실행기가 사용하는 단조 시계
+pub trait Clock {
+    /// 시계 기준점으로부터 지난 시간
+    fn now(&self) -> Duration;
+    fn sleep(&self, duration: Duration);
+}
+
+/// 실제 시간 (spin_sleep 사용)
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        spin_sleep::sleep(duration);
+    }
+}
+
+/// 테스트용 가짜 시계. sleep은 요청 시간 + overshoot 만큼 시간을 진행시킨다
+#[derive(Default)]
+pub struct FakeClock {
+    now: Cell<Duration>,
+    overshoot: Duration,
+}
+
+impl FakeClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 매 sleep마다 늦게 깨어나는 시간을 지정
+    pub fn with_overshoot(overshoot: Duration) -> Self {
+        Self { now: Cell::new(Duration::ZERO), overshoot }
+    }
+
+    /// 입력 지연 등을 흉내 내기 위해 시간을 직접 진행
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration + self.overshoot);
+    }
+}
+
+/// 키 이벤트를 실제로 보내는 쪽
+pub trait InputBackend {
+    /// 키를 보냈으면 true, 알 수 없는 키면 false
+    fn send(&self, key: &str, is_keyup: bool) -> bool;
+
+    /// 같은 시각의 이벤트를 한 번에 보낸다. 기본 구현은 하나씩 보낸다
+    fn send_batch(&self, events: &[(&str, bool)]) -> bool {
+        events.iter().fold(true, |ok, &(key, is_keyup)| self.send(key, is_keyup) && ok)
+    }
+
+    /// 타임라인을 만들 때 알 수 없는 키를 미리 걸러내기 위해 사용
+    fn is_known(&self, key: &str) -> bool {
+        SCANCODE.get(key).is_some()
+    }
+}
+
+/// SendInput으로 주입
+pub struct SendInputBackend;
+
+impl InputBackend for SendInputBackend {
+    fn send(&self, key: &str, is_keyup: bool) -> bool {
+        InputSystem::send_key(key, is_keyup)
+    }
+
+    fn send_batch(&self, events: &[(&str, bool)]) -> bool {
+        InputSystem::send_keys(events)
+    }
+}
+
+/// 아무것도 주입하지 않음 (측정용). 키 이름 확인만 한다
+pub struct NullBackend;
+
+impl InputBackend for NullBackend {
+    fn send(&self, key: &str, _is_keyup: bool) -> bool {
+        self.is_known(key)
+    }
+
+    fn send_batch(&self, events: &[(&str, bool)]) -> bool {
+        events.iter().all(|&(key, _)| self.is_known(key))
+    }
+}
+
+/// 실행 중 발생하는 이벤트
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecEvent<'a> {
+    /// 키 주입. drift_us는 예정 시각 대비 실제 시각 (마이크로초)
+    KeySent { key: &'a str, keyup: bool, drift_us: i64 },
+    UnknownKey { key: &'a str },
+    Progress { done: usize, total: usize },
+}
+
+/// 매크로 한 번 실행의 타이밍 통계 (단위: 마이크로초)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TimingStats {
+    pub events: usize,
+    pub mean_drift_us: i64,
+    pub p99_drift_us: i64,
+    pub max_drift_us: i64,
+    /// 액션 시간 합계
+    pub nominal_us: u64,
+    /// 실제 걸린 시간
+    pub actual_us: u64,
+    /// actual - nominal
+    pub overrun_us: i64,
+}
+
+impl TimingStats {
+    pub fn from_samples(drifts_us: &[i64], nominal: Duration, actual: Duration) -> Self {
+        let nominal_us = nominal.as_micros() as u64;
+        let actual_us = actual.as_micros() as u64;
+        let overrun_us = actual_us as i64 - nominal_us as i64;
+
+        if drifts_us.is_empty() {
+            return Self { nominal_us, actual_us, overrun_us, ..Self::default() };
+        }
+
+        let mut sorted = drifts_us.to_vec();
+        sorted.sort_unstable();
+        let p99_index = (sorted.len() * 99).div_ceil(100) - 1;
+
+        Self {
+            events: sorted.len(),
+            mean_drift_us: sorted.iter().sum::<i64>() / sorted.len() as i64,
+            p99_drift_us: sorted[p99_index],
+            max_drift_us: sorted[sorted.len() - 1],
+            nominal_us,
+            actual_us,
+            overrun_us,
+        }
+    }
+}
+
+/// 실행 결과
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunReport {
+    pub completed: bool,
+    /// 마지막으로 처리된 액션까지의 수
+    pub done: usize,
+    pub total: usize,
+    pub timing: TimingStats,
+}
+
+/// 매크로 실행기
+pub struct Executor<'a, C: Clock, B: InputBackend> {
+    clock: &'a C,
+    backend: &'a B,
+    cancel: &'a AtomicBool,
+}
+
+impl<'a, C: Clock, B: InputBackend> Executor<'a, C, B> {
+    pub fn new(clock: &'a C, backend: &'a B, cancel: &'a AtomicBool) -> Self {
+        Self { clock, backend, cancel }
+    }
+
+    pub fn run(&self, macro_item: &Macro, on_event: impl FnMut(ExecEvent<'_>)) -> RunReport {
+        let timeline = Timeline::build(macro_item, |key| self.backend.is_known(key));
+        self.run_timeline(&timeline, on_event)
+    }
+
+    /// 시작 시각을 기준으로 각 이벤트의 예정 시각까지 기다렸다가 보낸다.
+    /// 대기 오차나 SendInput 지연이 다음 이벤트로 누적되지 않는다.
+    /// 같은 시각에 예정된 이벤트(동시 입력, 0ms 연속 입력)는 한 번에 묶어 보낸다
+    pub fn run_timeline<'t>(
+        &self,
+        timeline: &'t Timeline,
+        mut on_event: impl FnMut(ExecEvent<'t>),
+    ) -> RunReport {
+        let total = timeline.actions;
+        let start = self.clock.now();
+        let mut drifts = Vec::with_capacity(timeline.events.len());
+        let mut held: Vec<&str> = Vec::new();
+        let mut batch: Vec<(&str, bool)> = Vec::new();
+
+        let mut done = 0;
+        let mut completed = true;
+        // 액션의 마지막 이벤트를 보내면 그 액션이 끝난 것 (press = "down" 액션은 누르기가 마지막)
+        let finishes = |index: usize| {
+            let action = timeline.events[index].action;
+            timeline.events.get(index + 1).is_none_or(|next| next.action != action)
+        };
+        let mut index = 0;
+
+        for group in timeline.events.chunk_by(|a, b| a.at == b.at) {
+            let scheduled_at = start + group[0].at;
+            if !self.sleep_until(scheduled_at) {
+                completed = false;
+                break;
+            }
+
+            batch.clear();
+            batch.extend(group.iter().filter_map(|event| match event.op {
+                KeyOp::Down => Some((event.key.as_str(), false)),
+                KeyOp::Up => Some((event.key.as_str(), true)),
+                KeyOp::Unknown => None,
+            }));
+            if !batch.is_empty() {
+                self.backend.send_batch(&batch);
+            }
+            let drift_us = self.clock.now().as_micros() as i64 - scheduled_at.as_micros() as i64;
+
+            for event in group {
+                match event.op {
+                    KeyOp::Down => {
+                        drifts.push(drift_us);
+                        held.push(&event.key);
+                        on_event(ExecEvent::KeySent { key: &event.key, keyup: false, drift_us });
+                    }
+                    KeyOp::Up => {
+                        drifts.push(drift_us);
+                        if let Some(pos) = held.iter().rposition(|k| *k == event.key) {
+                            held.remove(pos);
+                        }
+                        on_event(ExecEvent::KeySent { key: &event.key, keyup: true, drift_us });
+                    }
+                    KeyOp::Unknown => on_event(ExecEvent::UnknownKey { key: &event.key }),
+                }
+                if finishes(index) {
+                    done = event.action + 1;
+                    on_event(ExecEvent::Progress { done, total });
+                }
+                index += 1;
+            }
+        }
+
+        // 마지막 액션의 delay까지 기다려야 다음 실행과 겹치지 않는다
+        if completed && !self.sleep_until(start + timeline.duration) {
+            completed = false;
+        }
+
+        // 취소되어도 누른 키는 반드시 뗀다
+        if !held.is_empty() {
+            let releases: Vec<(&str, bool)> = held.iter().rev().map(|&key| (key, true)).collect();
+            self.backend.send_batch(&releases);
+            for (key, _) in releases {
+                on_event(ExecEvent::KeySent { key, keyup: true, drift_us: 0 });
+            }
+        }
+
+        let actual = self.clock.now().saturating_sub(start);
+        RunReport {
+            completed,
+            done,
+            total,
+            timing: TimingStats::from_samples(&drifts, timeline.duration, actual),
+        }
+    }
+
+    fn sleep_until(&self, deadline: Duration) -> bool {
+        sleep_until_cancellable(self.clock, deadline, self.cancel)
+    }
+}
+
+/// 취소 플래그를 확인하며 대기. 끝까지 기다렸으면 true
+pub fn sleep_cancellable<C: Clock>(clock: &C, duration: Duration, cancel: &AtomicBool) -> bool {
+    sleep_until_cancellable(clock, clock.now() + duration, cancel)
+}
+
+/// 시계 기준 deadline 시각까지 취소 플래그를 확인하며 대기
+pub fn sleep_until_cancellable<C: Clock>(clock: &C, deadline: Duration, cancel: &AtomicBool) -> bool {
+    loop {
+        if cancel.load(Ordering::SeqCst) {
+            return false;
+        }
+        let now = clock.now();
+        if now >= deadline {
+            return true;
+        }
+        clock.sleep((deadline - now).min(CANCEL_SLICE));
+    }
+}
@@ -0,0 +1,201 @@
+//! 트리거 키를 누르는 방식 (뗄 때, 길게, 두 번, 짧게) 인식.
+//! 시간은 밀리초 정수로 받으므로 가짜 이벤트 열로 시험할 수 있다
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::{MacroConfig, Passthrough};
+use crate::trigger::{self, Chord};
+
+/// 매크로가 실행되는 순간
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Gesture {
+    /// 누를 때 (누르고 있으면 자동 반복 입력마다)
+    #[default]
+    Press,
+    /// 뗄 때
+    Release,
+    /// ms보다 짧게 눌렀다 뗄 때. 같은 키에 double_tap이 있으면 두 번째 입력을 기다린 뒤 실행한다
+    Tap(u64),
+    /// ms 이상 누르고 있을 때 (떼기 전에 실행)
+    LongPress(u64),
+    /// 짧게 눌렀다 뗀 뒤 ms 안에 다시 누를 때
+    DoubleTap(u64),
+}
+
+impl Gesture {
+    pub fn is_press(&self) -> bool {
+        *self == Gesture::Press
+    }
+}
+
+/// 한 트리거 조합에 묶인 매크로 (config.macros 인덱스)
+#[derive(Debug, Default)]
+struct Bindings {
+    press: Option<usize>,
+    release: Option<usize>,
+    tap: Option<(u64, usize)>,
+    long_press: Option<(u64, usize)>,
+    double_tap: Option<(u64, usize)>,
+    /// 묶인 매크로 중 하나라도 passthrough = "before"면 키를 삼키지 않는다
+    pass: bool,
+}
+
+/// 눌려 있는 트리거
+#[derive(Debug)]
+struct Held {
+    chord: String,
+    since: u64,
+    long_fired: bool,
+    /// 두 번째 입력 (떼어도 tap이 아니다)
+    second: bool,
+}
+
+/// 뗀 뒤 두 번째 입력을 기다리는 트리거
+#[derive(Debug)]
+struct Pending {
+    released: u64,
+    /// double_tap이 안 되면 실행할 tap
+    tap: Option<usize>,
+}
+
+/// 키 이벤트에 대한 판단
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Decision {
+    /// 실행할 매크로 (config.macros 인덱스)
+    pub fire: Vec<usize>,
+    /// 트리거에 묶인 키라서 다른 프로그램에 넘기지 않는다
+    pub consume: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct GestureRecognizer {
+    bindings: HashMap<String, Bindings>,
+    /// 키 이름 → 누를 때의 조합
+    held: HashMap<String, Held>,
+    pending: HashMap<String, Pending>,
+}
+
+impl GestureRecognizer {
+    /// 활성 매크로의 트리거를 묶는다. 같은 조합과 방식이 겹치면 먼저 정의된 매크로가 이긴다.
+    /// 순서 트리거는 SequenceMatcher가 맡는다
+    pub fn new(config: &MacroConfig) -> Self {
+        let mut bindings: HashMap<String, Bindings> = HashMap::new();
+        let single = config.macros.iter().enumerate().filter(|(_, m)| m.enabled && !trigger::is_sequence(&m.trigger));
+        for (i, m) in single {
+            let entry = bindings.entry(trigger::normalize(&m.trigger)).or_default();
+            entry.pass |= m.passthrough == Passthrough::Before;
+            match m.gesture {
+                Gesture::Press => {
+                    entry.press.get_or_insert(i);
+                }
+                Gesture::Release => {
+                    entry.release.get_or_insert(i);
+                }
+                Gesture::Tap(ms) => {
+                    entry.tap.get_or_insert((ms, i));
+                }
+                Gesture::LongPress(ms) => {
+                    entry.long_press.get_or_insert((ms, i));
+                }
+                Gesture::DoubleTap(ms) => {
+                    entry.double_tap.get_or_insert((ms, i));
+                }
+            }
+        }
+        Self { bindings, ..Self::default() }
+    }
+
+    /// 트리거로 쓰이는 조합인지
+    pub fn is_bound(&self, chord: &str) -> bool {
+        self.bindings.contains_key(chord)
+    }
+
+    pub fn key_down(&mut self, chord: &Chord, now: u64) -> Decision {
+        let found = chord.names().into_iter().find_map(|name| self.bindings.get(&name).map(|b| (name, b)));
+        let Some((name, bindings)) = found else {
+            return Decision::default();
+        };
+        let mut decision = Decision { consume: !bindings.pass, ..Decision::default() };
+        decision.fire.extend(bindings.press);
+
+        // 자동 반복 입력
+        if self.held.contains_key(&chord.key) {
+            return decision;
+        }
+
+        let mut second = false;
+        if let Some(pending) = self.pending.remove(&name) {
+            match bindings.double_tap {
+                Some((ms, i)) if now.saturating_sub(pending.released) <= ms => {
+                    decision.fire.push(i);
+                    second = true;
+                }
+                // 기다리던 tap은 시간이 지났으면 먼저 실행
+                _ => decision.fire.extend(pending.tap),
+            }
+        }
+
+        self.held.insert(chord.key.clone(), Held { chord: name, since: now, long_fired: false, second });
+        decision
+    }
+
+    /// 다른 매크로가 실행 중일 때 누른 키. 실행할 수 없으므로 삼키지 않고 앱에 넘기며, 뗄 때도 넘긴다.
+    /// 실행 전부터 누르고 있던 키의 자동 반복은 누를 때처럼 계속 삼킨다
+    pub fn key_down_while_running(&mut self, chord: &Chord) -> Decision {
+        match self.held.get(&chord.key).and_then(|held| self.bindings.get(&held.chord)) {
+            Some(bindings) => Decision { consume: !bindings.pass, ..Decision::default() },
+            None => Decision::default(),
+        }
+    }
+
+    /// 키 이름만 받는다 (수정 키를 먼저 떼도 누를 때의 조합으로 처리)
+    pub fn key_up(&mut self, key: &str, now: u64) -> Decision {
+        let Some(held) = self.held.remove(key) else {
+            return Decision::default();
+        };
+        let Some(bindings) = self.bindings.get(&held.chord) else {
+            return Decision::default();
+        };
+        let mut decision = Decision { consume: !bindings.pass, ..Decision::default() };
+        decision.fire.extend(bindings.release);
+        if held.long_fired || held.second {
+            return decision;
+        }
+
+        let duration = now.saturating_sub(held.since);
+        let tap = bindings.tap.filter(|&(ms, _)| duration < ms).map(|(_, i)| i);
+        match bindings.double_tap {
+            Some((ms, _)) if duration < ms => {
+                self.pending.insert(held.chord, Pending { released: now, tap });
+            }
+            _ => decision.fire.extend(tap),
+        }
+        decision
+    }
+
+    /// 시간이 지나 결정되는 동작 (길게 누름, 기다린 tap). 주기적으로 부른다
+    pub fn poll(&mut self, now: u64) -> Vec<usize> {
+        let mut fire = Vec::new();
+        for held in self.held.values_mut().filter(|h| !h.long_fired && !h.second) {
+            if let Some((ms, i)) = self.bindings.get(&held.chord).and_then(|b| b.long_press)
+                && now.saturating_sub(held.since) >= ms
+            {
+                held.long_fired = true;
+                fire.push(i);
+            }
+        }
+
+        let bindings = &self.bindings;
+        self.pending.retain(|chord, pending| {
+            let window = bindings.get(chord).and_then(|b| b.double_tap).map_or(0, |(ms, _)| ms);
+            if now.saturating_sub(pending.released) <= window {
+                return true;
+            }
+            fire.extend(pending.tap);
+            false
+        });
+        fire
+    }
+}
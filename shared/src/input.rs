@@ -6,6 +6,11 @@ const KEYEVENTF_SCANCODE: u32 = 0x0008;
 const KEYEVENTF_KEYUP: u32 = 0x0002;
 const KEYEVENTF_EXTENDEDKEY: u32 = 0x0001;
 
+/// 주입한 키 이벤트의 dwExtraInfo ("KEYM"). 훅에서 자기가 보낸 키를 구분한다
+pub const INJECTED_MARK: usize = 0x4B45_594D;
+/// 키 바꾸기로 보낸 키의 dwExtraInfo ("KEYR"). 훅은 실제로 누른 키처럼 트리거로 본다
+pub const REMAP_MARK: usize = 0x4B45_5952;
+
 pub struct InputSystem;
 
 impl InputSystem {
@@ -24,8 +29,50 @@ impl InputSystem {
         true
     }
     
+    /// 여러 키 이벤트를 SendInput 한 번으로 주입 (순서 보장, 다른 입력이 끼어들지 않음).
+    /// 알 수 없는 키가 섞여 있으면 아무것도 보내지 않고 false
+    pub fn send_keys(events: &[(&str, bool)]) -> bool {
+        let mut inputs = Vec::with_capacity(events.len());
+        for &(key, is_keyup) in events {
+            let Some(scancode) = SCANCODE.get(key) else {
+                return false;
+            };
+            inputs.push(Self::key_input(scancode, SCANCODE.is_extended(key), is_keyup, INJECTED_MARK));
+        }
+        
+        if !inputs.is_empty() {
+            unsafe {
+                SendInput(&inputs, mem::size_of::<INPUT>() as i32);
+            }
+        }
+        
+        true
+    }
+    
+    /// 바꾼 키를 REMAP_MARK로 보낸다. 훅 안에서 바로 부르므로 스레드를 만들지 않는다
+    #[inline(always)]
+    pub fn send_remapped(key: &str, is_keyup: bool) -> bool {
+        let Some(scancode) = SCANCODE.get(key) else {
+            return false;
+        };
+        let input = Self::key_input(scancode, SCANCODE.is_extended(key), is_keyup, REMAP_MARK);
+        unsafe {
+            SendInput(&[input], mem::size_of::<INPUT>() as i32);
+        }
+        true
+    }
+    
     #[inline(always)]
     unsafe fn send_input_raw(scancode: u16, is_extended: bool, is_keyup: bool) {
+        let input = Self::key_input(scancode, is_extended, is_keyup, INJECTED_MARK);
+        
+        unsafe {
+            SendInput(&[input], mem::size_of::<INPUT>() as i32);
+        }
+    }
+    
+    #[inline(always)]
+    fn key_input(scancode: u16, is_extended: bool, is_keyup: bool, mark: usize) -> INPUT {
         let mut flags = KEYEVENTF_SCANCODE;
         if is_extended {
             flags |= KEYEVENTF_EXTENDEDKEY;
@@ -34,7 +81,7 @@ impl InputSystem {
             flags |= KEYEVENTF_KEYUP;
         }
         
-        let input = INPUT {
+        INPUT {
             r#type: INPUT_KEYBOARD,
             Anonymous: INPUT_0 {
                 ki: KEYBDINPUT {
@@ -42,13 +89,9 @@ impl InputSystem {
                     wScan: scancode,
                     dwFlags: KEYBD_EVENT_FLAGS(flags),
                     time: 0,
-                    dwExtraInfo: 0,
+                    dwExtraInfo: mark,
                 },
             },
-        };
-        
-        unsafe {
-            SendInput(&[input], mem::size_of::<INPUT>() as i32);
         }
     }
     
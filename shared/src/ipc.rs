@@ -0,0 +1,104 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+
+use crate::config::Macro;
+
+/// Editor → Runner 채널 주소 (로컬 전용)
+pub const IPC_ADDR: &str = "127.0.0.1:47615";
+
+/// Editor가 Runner에 보내는 요청
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcRequest {
+    /// 저장되지 않은 매크로를 카운트다운 후 바로 재생.
+    /// Runner의 설정은 저장된 것이므로 Editor가 자기 설정으로 펼쳐서 보낸다
+    Play { macro_item: Box<Macro>, countdown_ms: u64 },
+    /// 진행 중인 재생 취소
+    Cancel,
+}
+
+/// 요청과 토큰. 토큰이 다르면 Runner가 거부한다 (다른 로컬 프로그램이 키를 주입하지 못하게)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcEnvelope {
+    pub token: String,
+    pub request: IpcRequest,
+}
+
+/// Runner가 Editor로 돌려주는 진행 상황
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcEvent {
+    Countdown { remaining_ms: u64 },
+    Started { total: usize },
+    Progress { done: usize, total: usize },
+    Finished,
+    Cancelled,
+    Error { message: String },
+}
+
+/// Runner 채널에 연결
+pub fn connect() -> io::Result<TcpStream> {
+    TcpStream::connect(IPC_ADDR)
+}
+
+/// Runner가 시작할 때 토큰을 쓰는 파일 (사용자별 임시 폴더)
+pub fn token_path() -> PathBuf {
+    std::env::temp_dir().join("keym-ipc.token")
+}
+
+/// 요청 한 줄의 최대 크기. 넘으면 연결을 끊는다 (다른 프로그램이 끝없이 보내 메모리를 채우지 못하게)
+pub const MAX_REQUEST_BYTES: u64 = 16 * 1024 * 1024;
+
+/// 운영체제 난수로 만든 예측할 수 없는 128비트 토큰 (16진수)
+pub fn new_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("운영체제 난수를 읽을 수 없습니다");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn write_token(path: &Path, token: &str) -> io::Result<()> {
+    fs::write(path, token)
+}
+
+pub fn read_token(path: &Path) -> io::Result<String> {
+    Ok(fs::read_to_string(path)?.trim().to_string())
+}
+
+/// 토큰을 붙여 요청 전송
+pub fn send_request<W: Write>(writer: &mut W, token: String, request: IpcRequest) -> io::Result<()> {
+    write_message(writer, &IpcEnvelope { token, request })
+}
+
+/// 요청을 읽고 토큰을 확인한다. 토큰이 다르면 PermissionDenied, MAX_REQUEST_BYTES를 넘으면 InvalidData
+pub fn read_request<R: BufRead>(reader: &mut R, token: &str) -> io::Result<Option<IpcRequest>> {
+    let mut limited = reader.take(MAX_REQUEST_BYTES);
+    let Some(envelope) = read_message::<_, IpcEnvelope>(&mut limited)? else {
+        return Ok(None);
+    };
+    if envelope.token != token {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "토큰이 맞지 않습니다"));
+    }
+    Ok(Some(envelope.request))
+}
+
+/// 메시지 한 개를 JSON 한 줄로 전송
+pub fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// JSON 한 줄을 읽어 메시지로 변환 (연결이 닫히면 None)
+pub fn read_message<R: BufRead, T: DeserializeOwned>(reader: &mut R) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
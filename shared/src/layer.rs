@@ -0,0 +1,235 @@
+//! 레이어 (키를 누르고 있는 동안, 또는 눌러 전환한 뒤에만 쓰는 트리거와 키 바꾸기).
+//! 토글 키도 여기서 처리한다. 순수 상태 기계라 가짜 이벤트 열로 시험할 수 있다
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use crate::config::{Macro, MacroConfig};
+use crate::gesture::Gesture;
+use crate::trigger::{self, Chord};
+
+/// 레이어 키를 누르는 방식
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerMode {
+    /// 누르고 있는 동안만
+    #[default]
+    Momentary,
+    /// 누를 때마다 켜고 끈다
+    Toggle,
+}
+
+impl LayerMode {
+    pub fn is_momentary(&self) -> bool {
+        *self == LayerMode::Momentary
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    /// 매크로의 layer 값으로 쓰는 이름
+    pub name: String,
+    /// 레이어 키. 트리거처럼 소비되어 앱에 가지 않는다
+    pub key: String,
+    #[serde(default, skip_serializing_if = "LayerMode::is_momentary")]
+    pub mode: LayerMode,
+    /// 이 레이어에서 기본 remap 위에 더하는 키 바꾸기
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remap: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayerError {
+    /// 정의되지 않은 레이어를 쓰는 매크로
+    Unknown { name: String, layer: String },
+    /// 같은 이름의 레이어
+    Duplicate { layer: String },
+    /// 레이어 키가 토글 키, 다른 레이어 키, 트리거와 겹침
+    KeyTaken { layer: String, key: String, by: String },
+}
+
+impl fmt::Display for LayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayerError::Unknown { name, layer } => write!(f, "{}: 정의되지 않은 레이어입니다: {}", name, layer),
+            LayerError::Duplicate { layer } => write!(f, "레이어 이름이 겹칩니다: {}", layer),
+            LayerError::KeyTaken { layer, key, by } => {
+                write!(f, "레이어 {}의 키 {}가 {}와 겹칩니다", layer, key, by)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LayerError {}
+
+/// 레이어 이름, 레이어 키, 매크로의 layer 값을 확인한다
+pub fn check(config: &MacroConfig) -> Vec<LayerError> {
+    let mut errors = Vec::new();
+    let toggle = trigger::normalize(&config.toggle_key);
+    for (i, layer) in config.layers.iter().enumerate() {
+        let key = trigger::normalize(&layer.key);
+        let taken = |by: &str| LayerError::KeyTaken { layer: layer.name.clone(), key: layer.key.clone(), by: by.to_string() };
+        if config.layers[..i].iter().any(|other| other.name == layer.name) {
+            errors.push(LayerError::Duplicate { layer: layer.name.clone() });
+        }
+        if !config.toggle_key.is_empty() && key == toggle {
+            errors.push(taken("토글 키"));
+        }
+        if let Some(other) = config.layers[..i].iter().find(|other| trigger::normalize(&other.key) == key) {
+            errors.push(taken(&format!("레이어 {}", other.name)));
+        }
+        let first_steps = config.macros.iter().filter(|m| m.enabled).filter(|m| {
+            trigger::steps(&m.trigger).first().is_some_and(|chord| chord.to_string() == key)
+        });
+        for m in first_steps {
+            errors.push(taken(m.display_name()));
+        }
+    }
+    for m in config.macros.iter().filter(|m| !m.layer.is_empty()) {
+        if !config.layers.iter().any(|layer| layer.name == m.layer) {
+            errors.push(LayerError::Unknown { name: m.display_name().to_string(), layer: m.layer.clone() });
+        }
+    }
+    errors
+}
+
+/// 레이어에서 보이는 설정. 인덱스가 그대로이도록 매크로는 지우지 않고 enabled만 끈다.
+/// 레이어 매크로와 트리거, 누르는 방식이 같은 기본 매크로는 가려진다. None이면 기본 레이어
+pub fn view(config: &MacroConfig, layer: Option<&str>) -> MacroConfig {
+    let in_layer = |m: &Macro| m.enabled && layer == Some(m.layer.as_str());
+    let overrides: HashSet<(String, Gesture)> = config
+        .macros
+        .iter()
+        .filter(|m| in_layer(m))
+        .map(|m| (trigger::normalize(&m.trigger), m.gesture))
+        .collect();
+
+    let mut view = config.clone();
+    for m in &mut view.macros {
+        let base = m.layer.is_empty() && !overrides.contains(&(trigger::normalize(&m.trigger), m.gesture));
+        m.enabled &= base || in_layer(m);
+    }
+    if let Some(found) = config.layers.iter().find(|l| Some(l.name.as_str()) == layer) {
+        view.remap.extend(found.remap.clone());
+    }
+    view
+}
+
+/// 레이어 키와 토글 키에 대한 판단
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayerEvent {
+    /// 상관없는 키
+    Pass,
+    /// 레이어 키나 토글 키의 자동 반복, 떼기, 상태가 그대로인 입력 (소비만)
+    Consume,
+    /// 토글 키로 매크로를 켜거나 끔
+    Enabled(bool),
+    /// 활성 레이어가 바뀜 (None = 기본)
+    Active(Option<String>),
+}
+
+#[derive(Debug)]
+struct LayerKey {
+    /// 정규화한 조합
+    chord: String,
+    /// 뗄 때 비교하는 키 이름
+    key: String,
+    name: String,
+    mode: LayerMode,
+}
+
+#[derive(Debug)]
+pub struct LayerState {
+    layers: Vec<LayerKey>,
+    toggle_key: String,
+    enabled: bool,
+    /// 누르고 있는 momentary 레이어 (나중에 누른 것이 위)
+    held: Vec<usize>,
+    toggled: Option<usize>,
+    /// 소비한 뒤 아직 떼지 않은 키 이름
+    pressed: HashSet<String>,
+}
+
+impl LayerState {
+    pub fn new(config: &MacroConfig) -> Self {
+        Self {
+            layers: config
+                .layers
+                .iter()
+                .map(|l| LayerKey {
+                    chord: trigger::normalize(&l.key),
+                    key: Chord::parse(&l.key).key,
+                    name: l.name.clone(),
+                    mode: l.mode,
+                })
+                .collect(),
+            toggle_key: trigger::normalize(&config.toggle_key),
+            enabled: true,
+            held: Vec::new(),
+            toggled: None,
+            pressed: HashSet::new(),
+        }
+    }
+
+    /// 토글 키로 꺼져 있으면 false. 꺼져 있어도 토글 키는 받는다
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 활성 레이어 이름 (None = 기본)
+    pub fn active(&self) -> Option<&str> {
+        self.active_index().map(|i| self.layers[i].name.as_str())
+    }
+
+    fn active_index(&self) -> Option<usize> {
+        self.held.last().copied().or(self.toggled)
+    }
+
+    pub fn key_down(&mut self, chord: &Chord) -> LayerEvent {
+        if self.pressed.contains(&chord.key) {
+            return LayerEvent::Consume;
+        }
+        let names = chord.names();
+        if !self.toggle_key.is_empty() && names.contains(&self.toggle_key) {
+            self.pressed.insert(chord.key.clone());
+            self.enabled = !self.enabled;
+            return LayerEvent::Enabled(self.enabled);
+        }
+        if !self.enabled {
+            return LayerEvent::Pass;
+        }
+        let found = names.iter().find_map(|name| self.layers.iter().position(|layer| layer.chord == *name));
+        let Some(index) = found else {
+            return LayerEvent::Pass;
+        };
+
+        self.pressed.insert(chord.key.clone());
+        let before = self.active_index();
+        match self.layers[index].mode {
+            LayerMode::Momentary => self.held.push(index),
+            LayerMode::Toggle => self.toggled = if self.toggled == Some(index) { None } else { Some(index) },
+        }
+        self.changed(before)
+    }
+
+    /// 키 이름만 받는다 (수정 키를 먼저 떼도 누를 때의 키로 처리)
+    pub fn key_up(&mut self, key: &str) -> LayerEvent {
+        if !self.pressed.remove(key) {
+            return LayerEvent::Pass;
+        }
+        let before = self.active_index();
+        let released = self.held.iter().position(|&i| self.layers[i].key == key);
+        if let Some(position) = released {
+            self.held.remove(position);
+        }
+        self.changed(before)
+    }
+
+    fn changed(&self, before: Option<usize>) -> LayerEvent {
+        match self.active_index() {
+            after if after == before => LayerEvent::Consume,
+            after => LayerEvent::Active(after.map(|i| self.layers[i].name.clone())),
+        }
+    }
+}
@@ -2,8 +2,22 @@ pub mod config;
 pub mod input;
 pub mod scancode;
 pub mod mutex_file;
+pub mod ipc;
+pub mod executor;
+pub mod timeline;
+pub mod dsl;
+pub mod convert;
+pub mod trigger;
+pub mod gesture;
+pub mod sequence;
+pub mod remap;
+pub mod layer;
+pub mod running;
 
 pub use config::{MacroConfig, Macro, MacroAction};
 pub use input::InputSystem;
 pub use scancode::SCANCODE;
-pub use mutex_file::AppMutex;
\ No newline at end of file
+pub use mutex_file::AppMutex;
+pub use ipc::{IpcEvent, IpcRequest};
+pub use executor::{Executor, RunReport, TimingStats};
+pub use timeline::Timeline;
\ No newline at end of file
@@ -0,0 +1,93 @@
+//! 키 바꾸기 (capslock → esc). 누를 때 바꾼 키를 기억해 두었다가 뗄 때 같은 키를 뗀다
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::config::MacroConfig;
+use crate::scancode::SCANCODE;
+use crate::trigger;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemapError {
+    /// 보낼 수 없는 키 이름
+    UnknownKey { key: String },
+    /// 바뀐 키를 트리거로 쓰는 매크로 (실행되지 않는다)
+    Trigger { key: String, target: String, name: String },
+}
+
+impl fmt::Display for RemapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemapError::UnknownKey { key } => write!(f, "키 바꾸기: 알 수 없는 키입니다: {}", key),
+            RemapError::Trigger { key, target, name } => {
+                write!(f, "키 바꾸기: {}는 {}로 바뀌므로 {}의 트리거가 실행되지 않습니다", key, target, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RemapError {}
+
+/// 키 이름과 활성 매크로 트리거를 확인한다. 레이어의 remap은 그 레이어와 기본 매크로만 본다
+pub fn check(config: &MacroConfig) -> Vec<RemapError> {
+    let mut errors = Vec::new();
+    let tables = std::iter::once((None, &config.remap))
+        .chain(config.layers.iter().map(|layer| (Some(layer.name.as_str()), &layer.remap)));
+    for (layer, (key, target)) in tables.flat_map(|(layer, table)| table.iter().map(move |pair| (layer, pair))) {
+        for name in [key, target] {
+            if SCANCODE.get(name).is_none() {
+                errors.push(RemapError::UnknownKey { key: name.clone() });
+            }
+        }
+        let visible = |layer_name: &str| layer.is_none_or(|l| layer_name.is_empty() || layer_name == l);
+        for m in config.macros.iter().filter(|m| m.enabled && visible(&m.layer)) {
+            if trigger::steps(&m.trigger).iter().any(|chord| chord.key == *key) {
+                errors.push(RemapError::Trigger {
+                    key: key.clone(),
+                    target: target.clone(),
+                    name: m.display_name().to_string(),
+                });
+            }
+        }
+    }
+    errors
+}
+
+#[derive(Debug, Default)]
+pub struct Remapper {
+    table: HashMap<String, String>,
+    /// 누른 키 → 누를 때 보낸 키
+    held: HashMap<String, String>,
+}
+
+impl Remapper {
+    /// 보낼 수 없는 키가 있는 항목은 뺀다
+    pub fn new(config: &MacroConfig) -> Self {
+        let table = config
+            .remap
+            .iter()
+            .filter(|(key, target)| SCANCODE.get(key).is_some() && SCANCODE.get(target).is_some())
+            .map(|(key, target)| (key.clone(), target.clone()))
+            .collect();
+        Self { table, held: HashMap::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// 대신 누를 키. 자동 반복은 누를 때와 같은 키
+    pub fn key_down(&mut self, key: &str) -> Option<String> {
+        if let Some(target) = self.held.get(key) {
+            return Some(target.clone());
+        }
+        let target = self.table.get(key)?.clone();
+        self.held.insert(key.to_string(), target.clone());
+        Some(target)
+    }
+
+    /// 대신 뗄 키. 누르는 동안 표가 바뀌거나 꺼져도 누를 때 보낸 키를 뗀다
+    pub fn key_up(&mut self, key: &str) -> Option<String> {
+        self.held.remove(key)
+    }
+}
@@ -0,0 +1,79 @@
+//! 실행 중인 매크로. 매크로가 주입한 키로 시작한 매크로 (chain_triggers)는 실행 중인 매크로와 함께 돌고,
+//! 그 밖의 트리거는 실행 중이면 무시한다. 순수 상태라 스레드 없이 시험할 수 있다
+
+use std::fmt;
+
+/// 체인으로 함께 도는 매크로 수 한도 (처음 시작한 매크로 포함)
+pub const MAX_CHAIN_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartError {
+    /// 다른 매크로가 실행 중
+    Busy,
+    /// chain_triggers가 꺼져 있는데 매크로가 주입한 키로 시작
+    Injected,
+    /// 실행 중인 매크로를 체인으로 다시 시작 (a → b → a)
+    Cycle,
+    /// MAX_CHAIN_DEPTH를 넘는 체인
+    TooDeep,
+}
+
+impl fmt::Display for StartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartError::Busy => f.write_str("이미 매크로 실행 중입니다"),
+            StartError::Injected => f.write_str("매크로가 누른 키로는 실행하지 않습니다 (chain_triggers)"),
+            StartError::Cycle => f.write_str("체인 트리거가 실행 중인 매크로를 다시 실행합니다"),
+            StartError::TooDeep => write!(f, "체인 트리거가 너무 깊습니다 (최대 {}개)", MAX_CHAIN_DEPTH),
+        }
+    }
+}
+
+impl std::error::Error for StartError {}
+
+#[derive(Debug, Default)]
+pub struct RunningMacros {
+    chain_triggers: bool,
+    /// 실행 중인 매크로 (config.macros 인덱스, None = 테스트 재생). 시작한 순서
+    running: Vec<Option<usize>>,
+}
+
+impl RunningMacros {
+    pub fn new(chain_triggers: bool) -> Self {
+        Self { chain_triggers, running: Vec::new() }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// 실행을 점유한다. 아무것도 실행 중이 아니었으면 Ok(true)로, 이때만 이전 취소 요청을 지운다
+    pub fn start(&mut self, index: Option<usize>, injected: bool) -> Result<bool, StartError> {
+        if injected && !self.chain_triggers {
+            return Err(StartError::Injected);
+        }
+        if self.running.is_empty() {
+            self.running.push(index);
+            return Ok(true);
+        }
+        if !injected {
+            return Err(StartError::Busy);
+        }
+        if index.is_some() && self.running.contains(&index) {
+            return Err(StartError::Cycle);
+        }
+        if self.running.len() >= MAX_CHAIN_DEPTH {
+            return Err(StartError::TooDeep);
+        }
+        self.running.push(index);
+        Ok(false)
+    }
+
+    /// 끝난 실행을 뺀다. 모두 끝났으면 true
+    pub fn finish(&mut self, index: Option<usize>) -> bool {
+        if let Some(position) = self.running.iter().position(|&r| r == index) {
+            self.running.remove(position);
+        }
+        self.running.is_empty()
+    }
+}
@@ -0,0 +1,126 @@
+//! 순서 트리거 ("f12 g 1": f12, g, 1을 차례로 누르면 실행).
+//! 시간은 밀리초 정수로 받으므로 가짜 이벤트 열로 시험할 수 있다
+
+use std::collections::HashSet;
+
+use crate::config::{MacroConfig, Passthrough};
+use crate::trigger::{self, Chord};
+
+/// 키 이벤트에 대한 판단
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SequenceDecision {
+    /// 실행할 매크로 (config.macros 인덱스)
+    pub fire: Option<usize>,
+    /// 순서의 일부라서 다른 프로그램에 넘기지 않는다
+    pub consume: bool,
+    /// 순서가 이어지지 않아 돌려보낼 조합 (누른 순서대로, 수정 키와 함께 눌렀다 떼기)
+    pub replay: Vec<Chord>,
+}
+
+#[derive(Debug)]
+pub struct SequenceMatcher {
+    /// (단계별 조합, 매크로 인덱스, 마지막 키를 넘기는지)
+    sequences: Vec<(Vec<String>, usize, bool)>,
+    timeout_ms: u64,
+    /// 지금까지 맞은 단계
+    buffer: Vec<Chord>,
+    last_at: u64,
+    /// 삼킨 뒤 아직 떼지 않은 키. 자동 반복과 뗄 때도 삼킨다
+    held: HashSet<String>,
+}
+
+impl SequenceMatcher {
+    /// 활성 매크로 중 두 단계 이상인 트리거만 쓴다. 같은 순서는 먼저 정의된 매크로가 이긴다
+    pub fn new(config: &MacroConfig) -> Self {
+        let mut sequences: Vec<(Vec<String>, usize, bool)> = Vec::new();
+        for (i, m) in config.macros.iter().enumerate().filter(|(_, m)| m.enabled) {
+            let steps: Vec<String> = trigger::steps(&m.trigger).iter().map(Chord::to_string).collect();
+            if steps.len() > 1 && !sequences.iter().any(|(existing, _, _)| *existing == steps) {
+                sequences.push((steps, i, m.passthrough == Passthrough::Before));
+            }
+        }
+        Self {
+            sequences,
+            timeout_ms: config.sequence_timeout_ms,
+            buffer: Vec::new(),
+            last_at: 0,
+            held: HashSet::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequences.is_empty()
+    }
+
+    pub fn key_down(&mut self, chord: &Chord, now: u64) -> SequenceDecision {
+        // 자동 반복
+        if self.held.contains(&chord.key) {
+            return SequenceDecision { consume: true, ..SequenceDecision::default() };
+        }
+
+        let mut replay = self.poll(now);
+        let step = chord.to_string();
+        let buffered: Vec<String> = self.buffer.iter().map(Chord::to_string).collect();
+        let mut candidate: Vec<&str> = buffered.iter().map(String::as_str).collect();
+        candidate.push(&step);
+
+        let mut decision = match self.advance(&candidate) {
+            Some(decision) => decision,
+            None => {
+                // 이어지지 않으면 모아 둔 키를 돌려보내고 이번 키로 다시 시작
+                replay.append(&mut self.buffer);
+                match self.advance(&[&step]) {
+                    Some(decision) => decision,
+                    // 앞서 삼킨 키보다 먼저 지나가지 않도록 이번 키도 함께 돌려보낸다
+                    None if !replay.is_empty() => {
+                        replay.push(chord.clone());
+                        self.held.insert(chord.key.clone());
+                        return SequenceDecision { consume: true, replay, ..SequenceDecision::default() };
+                    }
+                    None => return SequenceDecision { replay, ..SequenceDecision::default() },
+                }
+            }
+        };
+
+        if decision.fire.is_some() {
+            self.buffer.clear();
+        } else {
+            self.buffer.push(chord.clone());
+            self.last_at = now;
+        }
+        if decision.consume {
+            self.held.insert(chord.key.clone());
+        }
+        decision.replay = replay;
+        decision
+    }
+
+    /// 끝까지 맞으면 실행, 앞부분이 맞으면 기다린다. 아니면 None
+    fn advance(&self, candidate: &[&str]) -> Option<SequenceDecision> {
+        let mut prefix = false;
+        for (steps, index, pass) in &self.sequences {
+            if steps.len() < candidate.len() || !steps.iter().zip(candidate).all(|(a, b)| a == b) {
+                continue;
+            }
+            if steps.len() == candidate.len() {
+                // 넘기는 매크로는 마지막 키만 그대로 보낸다
+                return Some(SequenceDecision { fire: Some(*index), consume: !pass, ..SequenceDecision::default() });
+            }
+            prefix = true;
+        }
+        prefix.then(|| SequenceDecision { consume: true, ..SequenceDecision::default() })
+    }
+
+    /// 삼킨 키를 뗄 때도 삼킨다
+    pub fn key_up(&mut self, key: &str) -> bool {
+        self.held.remove(key)
+    }
+
+    /// 시간 안에 다음 단계가 없으면 모아 둔 키를 돌려보낸다. 주기적으로 부른다
+    pub fn poll(&mut self, now: u64) -> Vec<Chord> {
+        if self.buffer.is_empty() || now.saturating_sub(self.last_at) <= self.timeout_ms {
+            return Vec::new();
+        }
+        std::mem::take(&mut self.buffer)
+    }
+}
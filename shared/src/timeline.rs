@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use crate::config::{Macro, Press};
+
+/// 예정된 이벤트 종류
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOp {
+    Down,
+    Up,
+    /// 알 수 없는 키. 시간은 차지하지 않고 건너뛴다
+    Unknown,
+}
+
+/// 시작 시점 기준 절대 시각에 예정된 이벤트
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledEvent {
+    pub at: Duration,
+    pub key: String,
+    pub op: KeyOp,
+    /// 이 이벤트가 속한 액션 번호
+    pub action: usize,
+}
+
+/// 매크로 한 번 실행분의 이벤트 목록
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timeline {
+    pub events: Vec<ScheduledEvent>,
+    /// 마지막 액션의 delay까지 포함한 전체 길이
+    pub duration: Duration,
+    pub actions: usize,
+}
+
+impl Timeline {
+    /// hold_ms / delay_ms를 누적해 각 이벤트의 절대 시각을 계산.
+    /// call, repeat, group은 MacroConfig::expand로 먼저 펼쳐야 한다
+    pub fn build(macro_item: &Macro, is_known: impl Fn(&str) -> bool) -> Self {
+        let mut events = Vec::with_capacity(macro_item.actions.len() * 2);
+        let mut at = Duration::ZERO;
+
+        for (index, action) in macro_item.actions.iter().enumerate() {
+            if !is_known(&action.key) {
+                events.push(ScheduledEvent {
+                    at,
+                    key: action.key.clone(),
+                    op: KeyOp::Unknown,
+                    action: index,
+                });
+                continue;
+            }
+
+            let mut push = |at, op| {
+                events.push(ScheduledEvent {
+                    at,
+                    key: action.key.clone(),
+                    op,
+                    action: index,
+                })
+            };
+            match action.press {
+                Press::Tap => {
+                    push(at, KeyOp::Down);
+                    at += Duration::from_millis(action.hold_ms);
+                    push(at, KeyOp::Up);
+                }
+                Press::Down => push(at, KeyOp::Down),
+                Press::Up => push(at, KeyOp::Up),
+            }
+            at += Duration::from_millis(action.delay_ms);
+        }
+
+        Self {
+            events,
+            duration: at,
+            actions: macro_item.actions.len(),
+        }
+    }
+}
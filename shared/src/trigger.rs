@@ -0,0 +1,108 @@
+//! 트리거 표기. 조합키는 "ctrl+shift+1"처럼 수정 키를 앞에 붙인다
+
+use std::fmt;
+
+pub const CTRL: u8 = 1;
+pub const ALT: u8 = 2;
+pub const SHIFT: u8 = 4;
+
+/// 표기 순서
+const MODIFIERS: [(&str, u8); 3] = [("ctrl", CTRL), ("alt", ALT), ("shift", SHIFT)];
+
+/// 수정 키이면 해당 비트
+pub fn modifier_bit(key: &str) -> Option<u8> {
+    MODIFIERS.iter().find(|(name, _)| *name == key).map(|&(_, bit)| bit)
+}
+
+/// 수정 키 조합 + 키 하나
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub modifiers: u8,
+    pub key: String,
+}
+
+impl Chord {
+    /// 눌린 수정 키와 새로 눌린 키. 키 자신은 조합에서 뺀다 ("shift" 단독 트리거)
+    pub fn new(modifiers: u8, key: impl Into<String>) -> Self {
+        let key = key.into();
+        let own = modifier_bit(&key).unwrap_or(0);
+        Self { modifiers: modifiers & !own, key }
+    }
+
+    /// "shift+ctrl+1" → ctrl+shift, "1". "num+"처럼 +로 끝나는 키 이름은 그대로 둔다
+    pub fn parse(trigger: &str) -> Self {
+        let mut modifiers = 0;
+        let mut rest = trigger.trim();
+        'strip: loop {
+            for &(name, bit) in &MODIFIERS {
+                if let Some(after) = rest.strip_prefix(name).and_then(|r| r.trim_start().strip_prefix('+'))
+                    && !after.trim().is_empty()
+                {
+                    modifiers |= bit;
+                    rest = after.trim_start();
+                    continue 'strip;
+                }
+            }
+            break;
+        }
+        Self::new(modifiers, rest)
+    }
+
+    /// 이 입력에 맞는 트리거 표기 (앞의 것이 우선). 수정 키 없이 적은 트리거는
+    /// 수정 키를 누른 채로도 맞는다 ("1"은 shift+1에도 실행되고, "shift+1"이 있으면 그쪽이 이긴다)
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![self.to_string()];
+        if self.modifiers != 0 {
+            names.push(self.key.clone());
+        }
+        names
+    }
+
+    /// 이 트리거가 `pressed` 입력에 실행되는지 (runner와 같은 규칙)
+    pub fn matches(&self, pressed: &Chord) -> bool {
+        pressed.names().contains(&self.to_string())
+    }
+
+    /// 조합의 수정 키 이름 (표기 순서)
+    pub fn modifier_keys(&self) -> Vec<&'static str> {
+        MODIFIERS.iter().filter(|&&(_, bit)| self.modifiers & bit != 0).map(|&(name, _)| name).collect()
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &(name, bit) in &MODIFIERS {
+            if self.modifiers & bit != 0 {
+                write!(f, "{}+", name)?;
+            }
+        }
+        f.write_str(&self.key)
+    }
+}
+
+/// 비교용 표기 ("shift + ctrl+1" → "ctrl+shift+1", "f12  g" → "f12 g")
+pub fn normalize(trigger: &str) -> String {
+    let steps: Vec<String> = steps(trigger).iter().map(Chord::to_string).collect();
+    steps.join(" ")
+}
+
+/// 공백으로 나눈 순서 트리거 ("f12 g 1"). "ctrl + 1"처럼 + 주변의 공백은 한 단계로 본다
+pub fn steps(trigger: &str) -> Vec<Chord> {
+    let mut steps: Vec<String> = Vec::new();
+    for token in trigger.split_whitespace() {
+        let joins = steps.last().is_some_and(|prev| {
+            token.starts_with('+')
+                || prev.strip_suffix('+').is_some_and(|m| modifier_bit(m.rsplit('+').next().unwrap_or(m)).is_some())
+        });
+        match steps.last_mut() {
+            Some(prev) if joins => prev.push_str(token),
+            _ => steps.push(token.to_string()),
+        }
+    }
+    steps.iter().map(|step| Chord::parse(step)).collect()
+}
+
+/// 두 단계 이상인 순서 트리거인지
+pub fn is_sequence(trigger: &str) -> bool {
+    steps(trigger).len() > 1
+}
@@ -0,0 +1,155 @@
+use shared::config::{BundleError, MacroBundle, Resolution, BUNDLE_VERSION};
+use shared::gesture::Gesture;
+use shared::{Macro, MacroAction, MacroConfig};
+use std::collections::BTreeMap;
+
+mod common;
+use common::{config_of, with_id};
+
+fn source() -> MacroConfig {
+    config_of(vec![
+        with_id("login", "f12", vec![MacroAction::new("enter", 0, 0)]),
+        with_id("hunt", "1", vec![MacroAction::new("a", 10, 0), MacroAction::call("login", 0)]),
+        with_id("other", "2", vec![MacroAction::new("b", 0, 0)]),
+    ])
+}
+
+fn triggers(config: &MacroConfig) -> Vec<(&str, &str)> {
+    config.macros.iter().map(|m| (m.id.as_str(), m.trigger.as_str())).collect()
+}
+
+#[test]
+fn export_includes_called_macros_and_round_trips() {
+    let bundle = MacroBundle::export(&source(), &["hunt".to_string()]);
+    let ids: Vec<&str> = bundle.macros.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["login", "hunt"]);
+
+    let parsed = MacroBundle::parse(&bundle.to_json()).unwrap();
+    assert_eq!(parsed.version, BUNDLE_VERSION);
+    assert_eq!(parsed.macros[1].actions, bundle.macros[1].actions);
+
+    let mut target = MacroConfig::default();
+    let imported = target.import_bundle(parsed, &BTreeMap::new()).unwrap();
+    assert_eq!(imported, ["login", "hunt"]);
+    assert!(target.check_macros().is_empty());
+}
+
+#[test]
+fn other_files_and_versions_are_rejected() {
+    assert!(matches!(MacroBundle::parse("{\"format\": \"x\", \"version\": 1}"), Err(BundleError::Format(_))));
+    assert!(matches!(
+        MacroBundle::parse("{\"format\": \"keym-macros\", \"version\": 99}"),
+        Err(BundleError::Version(99))
+    ));
+    assert!(matches!(MacroBundle::parse("not json"), Err(BundleError::Invalid(_))));
+}
+
+#[test]
+fn conflicts_must_be_resolved() {
+    let bundle = MacroBundle::export(&source(), &["hunt".to_string()]);
+    let target = config_of(vec![with_id("mine", "1", vec![MacroAction::new("z", 0, 0)])]);
+
+    let conflicts = bundle.conflicts(&target);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!((conflicts[0].id.as_str(), conflicts[0].existing_id.as_str()), ("hunt", "mine"));
+
+    let error = target.clone().import_bundle(bundle, &BTreeMap::new()).unwrap_err();
+    assert!(matches!(error, BundleError::Unresolved { trigger } if trigger == "1"));
+}
+
+#[test]
+fn skip_rename_and_replace() {
+    let bundle = MacroBundle::export(&source(), &["hunt".to_string(), "other".to_string()]);
+    let existing = config_of(vec![
+        with_id("mine", "1", vec![MacroAction::new("z", 0, 0)]),
+        with_id("login", "f12", vec![MacroAction::new("esc", 0, 0)]),
+    ]);
+
+    let mut skip = existing.clone();
+    let resolutions = BTreeMap::from([
+        ("hunt".to_string(), Resolution::Skip),
+        ("login".to_string(), Resolution::Skip),
+    ]);
+    skip.import_bundle(bundle.clone(), &resolutions).unwrap();
+    assert_eq!(triggers(&skip), [("mine", "1"), ("login", "f12"), ("other", "2")]);
+
+    // 바꾼 트리거로 함께 둔다. 겹치는 id는 새로 만들고 call도 따라간다
+    let mut rename = existing.clone();
+    let resolutions = BTreeMap::from([
+        ("hunt".to_string(), Resolution::Rename { trigger: "ctrl+1".to_string() }),
+        ("login".to_string(), Resolution::Rename { trigger: "f11".to_string() }),
+    ]);
+    rename.import_bundle(bundle.clone(), &resolutions).unwrap();
+    let new_login = rename.macros[2].id.clone();
+    assert_ne!(new_login, "login");
+    assert_eq!(rename.macros[2].trigger, "f11");
+    assert_eq!(rename.macros[3].trigger, "ctrl+1");
+    assert_eq!(rename.macros[3].actions[1].call.as_deref(), Some(new_login.as_str()));
+
+    let mut replace = existing.clone();
+    let resolutions = BTreeMap::from([
+        ("hunt".to_string(), Resolution::Replace),
+        ("login".to_string(), Resolution::Replace),
+    ]);
+    replace.import_bundle(bundle.clone(), &resolutions).unwrap();
+    assert_eq!(triggers(&replace), [("mine", "1"), ("login", "f12"), ("other", "2")]);
+    assert_eq!(replace.macros[0].actions[0], MacroAction::new("a", 10, 0));
+    assert_eq!(replace.macros[1].actions[0], MacroAction::new("enter", 0, 0));
+
+    // 바꾼 트리거가 다시 겹치면 거부
+    let resolutions = BTreeMap::from([
+        ("hunt".to_string(), Resolution::Rename { trigger: "2".to_string() }),
+        ("login".to_string(), Resolution::Skip),
+    ]);
+    let error = existing.clone().import_bundle(bundle, &resolutions).unwrap_err();
+    assert!(matches!(error, BundleError::Taken { .. }));
+}
+
+#[test]
+fn conflicts_follow_layer_gesture_and_notation() {
+    let bundle = MacroBundle {
+        macros: vec![
+            with_id("chord", "shift+ctrl+1", Vec::new()),
+            Macro { layer: "nav".to_string(), ..with_id("nav", "2", Vec::new()) },
+            Macro { gesture: Gesture::LongPress(300), ..with_id("hold", "3", Vec::new()) },
+        ],
+        ..MacroBundle::export(&MacroConfig::default(), &[])
+    };
+    let target = config_of(vec![
+        with_id("mine", "ctrl+shift+1", Vec::new()),
+        with_id("plain", "2", Vec::new()),
+        with_id("tap", "3", Vec::new()),
+    ]);
+
+    let conflicts = bundle.conflicts(&target);
+    let pairs: Vec<(&str, &str)> = conflicts.iter().map(|c| (c.id.as_str(), c.existing_id.as_str())).collect();
+    assert_eq!(pairs, [("chord", "mine")]);
+}
+
+#[test]
+fn duplicates_inside_the_bundle_are_conflicts() {
+    let bundle = MacroBundle {
+        macros: vec![
+            with_id("first", "f5", vec![MacroAction::new("a", 0, 0)]),
+            with_id("second", "f5", vec![MacroAction::new("b", 0, 0)]),
+            with_id("caller", "f6", vec![MacroAction::call("second", 0)]),
+        ],
+        ..MacroBundle::export(&MacroConfig::default(), &[])
+    };
+    let conflicts = bundle.conflicts(&MacroConfig::default());
+    assert_eq!(conflicts.len(), 1);
+    assert!(conflicts[0].in_bundle);
+    assert_eq!((conflicts[0].id.as_str(), conflicts[0].existing_id.as_str()), ("second", "first"));
+
+    // 건너뛰면 second를 부르던 call은 first를 부른다
+    let mut skip = MacroConfig::default();
+    skip.import_bundle(bundle.clone(), &BTreeMap::from([("second".to_string(), Resolution::Skip)])).unwrap();
+    assert_eq!(triggers(&skip), [("first", "f5"), ("caller", "f6")]);
+    assert_eq!(skip.macros[1].actions[0].call.as_deref(), Some("first"));
+
+    // 덮어쓰면 뒤의 매크로만 남는다
+    let mut replace = MacroConfig::default();
+    replace.import_bundle(bundle, &BTreeMap::from([("second".to_string(), Resolution::Replace)])).unwrap();
+    assert_eq!(triggers(&replace), [("second", "f5"), ("caller", "f6")]);
+    assert_eq!(replace.macros[0].actions[0].key, "b");
+}
@@ -0,0 +1,49 @@
+//! 여러 테스트 파일이 함께 쓰는 준비 함수. 파일마다 쓰는 것만 달라 경고는 끈다
+#![allow(dead_code)]
+
+use shared::{Macro, MacroAction, MacroConfig};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 테스트마다 따로 쓰는 임시 디렉터리
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("keym-test-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 상위 디렉터리까지 만들어 쓴다
+pub fn write(path: &Path, content: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+pub fn with_id(id: &str, trigger: &str, actions: Vec<MacroAction>) -> Macro {
+    Macro {
+        id: id.to_string(),
+        actions,
+        ..Macro::new(trigger)
+    }
+}
+
+/// 트리거는 모두 "1"
+pub fn named(id: &str, name: &str, actions: Vec<MacroAction>) -> Macro {
+    Macro {
+        name: name.to_string(),
+        ..with_id(id, "1", actions)
+    }
+}
+
+pub fn config_of(macros: Vec<Macro>) -> MacroConfig {
+    MacroConfig { macros, ..MacroConfig::default() }
+}
+
+/// 트리거만 다른 빈 매크로들
+pub fn config_of_triggers(triggers: &[&str]) -> MacroConfig {
+    config_of(triggers.iter().map(|&t| Macro::new(t)).collect())
+}
+
+pub fn tap(key: &str) -> MacroAction {
+    MacroAction::new(key, 0, 0)
+}
@@ -0,0 +1,269 @@
+use shared::config::{substitute, ExpandError, ParamValue, MAX_CALL_DEPTH, MAX_EXPANDED_ACTIONS};
+use shared::{Macro, MacroAction, MacroConfig};
+
+mod common;
+use common::{config_of, named, temp_dir};
+
+fn keys(m: &Macro) -> Vec<(&str, u64)> {
+    m.actions.iter().map(|a| (a.key.as_str(), a.delay_ms)).collect()
+}
+
+#[test]
+fn call_is_expanded_inline() {
+    let config = config_of(vec![
+        named("login", "로그인", vec![MacroAction::new("a", 10, 5), MacroAction::new("b", 10, 5)]),
+        named(
+            "main",
+            "",
+            vec![MacroAction::new("x", 10, 0), MacroAction::call("로그인", 100), MacroAction::new("y", 10, 0)],
+        ),
+    ]);
+
+    assert!(config.check_macros().is_empty());
+    let expanded = config.expand(&config.macros[1]).unwrap();
+    // call의 delay는 호출된 시퀀스의 마지막 액션 뒤에 붙는다
+    assert_eq!(keys(&expanded), vec![("x", 0), ("a", 5), ("b", 105), ("y", 0)]);
+    assert_eq!(expanded.id, "main");
+}
+
+#[test]
+fn call_parses_from_toml() {
+    let config = MacroConfig::parse(
+        r#"
+version = 2
+
+[[macros]]
+id = "menu"
+trigger = "f5"
+
+[[macros.actions]]
+key = "esc"
+
+[[macros]]
+id = "main"
+trigger = "1"
+
+[[macros.actions]]
+call = "menu"
+delay_ms = 20
+"#,
+    )
+    .unwrap();
+
+    let expanded = config.expand(config.get_macro("1").unwrap()).unwrap();
+    assert_eq!(keys(&expanded), vec![("esc", 20)]);
+}
+
+#[test]
+fn cycles_are_reported_once() {
+    let config = config_of(vec![
+        named("a", "", vec![MacroAction::call("b", 0)]),
+        named("b", "", vec![MacroAction::call("c", 0)]),
+        named("c", "", vec![MacroAction::call("a", 0)]),
+        named("d", "", vec![MacroAction::call("d", 0)]),
+        named("e", "", vec![MacroAction::call("없음", 0)]),
+    ]);
+
+    let errors = config.check_macros();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors.contains(&ExpandError::Missing { from: "1".to_string(), target: "없음".to_string() }));
+    assert!(errors.iter().any(|e| matches!(e, ExpandError::Cycle { chain } if chain.len() == 4)));
+    assert!(errors.iter().any(|e| matches!(e, ExpandError::Cycle { chain } if chain.len() == 2)));
+
+    assert!(matches!(config.expand(&config.macros[0]), Err(ExpandError::Cycle { .. })));
+}
+
+#[test]
+fn wide_call_graph_is_checked_quickly() {
+    // 단계마다 두 매크로가 다음 단계의 두 매크로를 모두 부른다 (경로는 2^40개)
+    let levels = 40;
+    let mut macros = Vec::new();
+    for level in 0..levels {
+        for side in ["l", "r"] {
+            let calls = ["l", "r"].map(|next| MacroAction::call(format!("{}{}", next, level + 1), 0));
+            macros.push(named(&format!("{}{}", side, level), "", calls.to_vec()));
+        }
+    }
+    macros.push(named(&format!("l{}", levels), "", vec![MacroAction::new("a", 0, 0)]));
+    macros.push(named(&format!("r{}", levels), "", vec![MacroAction::call("l0", 0)]));
+    let config = config_of(macros);
+
+    let cycles: Vec<usize> = config
+        .check_macros()
+        .into_iter()
+        .map(|e| match e {
+            ExpandError::Cycle { chain } => chain.len(),
+            other => panic!("{}", other),
+        })
+        .collect();
+    assert_eq!(cycles, [levels + 2]);
+}
+
+#[test]
+fn nesting_deeper_than_limit_fails_at_runtime() {
+    // m0 → m1 → ... → m(N+1): 순환은 없지만 너무 깊다
+    let depth = MAX_CALL_DEPTH + 1;
+    let mut macros: Vec<Macro> = (0..depth)
+        .map(|i| named(&format!("m{}", i), "", vec![MacroAction::call(format!("m{}", i + 1), 0)]))
+        .collect();
+    macros.push(named(&format!("m{}", depth), "", vec![MacroAction::new("a", 10, 0)]));
+    let config = config_of(macros);
+
+    assert!(config.check_macros().is_empty());
+    assert!(matches!(config.expand(&config.macros[0]), Err(ExpandError::TooDeep { .. })));
+    assert_eq!(keys(&config.expand(&config.macros[1]).unwrap()), vec![("a", 0)]);
+}
+
+#[test]
+fn one_definition_bound_to_several_triggers_with_arguments() {
+    let config = MacroConfig::parse(
+        r#"
+version = 2
+
+[[macros]]
+id = "spam"
+trigger = "f12"
+enabled = false
+
+[macros.params]
+key = "a"
+count = 2
+
+[[macros.actions]]
+key = "$key"
+repeat = "$count"
+delay_ms = 10
+
+[[macros]]
+id = "one"
+trigger = "1"
+
+[[macros.actions]]
+call = "spam"
+args = { key = "e", count = 3 }
+
+[[macros]]
+id = "two"
+trigger = "2"
+
+[[macros.actions]]
+call = "spam"
+"#,
+    )
+    .unwrap();
+
+    assert!(config.check_macros().is_empty());
+    let one = config.expand(config.get_macro("1").unwrap()).unwrap();
+    assert_eq!(keys(&one), vec![("e", 10), ("e", 10), ("e", 10)]);
+    // 인자가 없으면 기본값
+    let two = config.expand(config.get_macro("2").unwrap()).unwrap();
+    assert_eq!(keys(&two), vec![("a", 10), ("a", 10)]);
+}
+
+#[test]
+fn string_arguments_see_caller_params() {
+    let mut inner = named("inner", "", vec![MacroAction::new("${k}", 0, 0)]);
+    inner.params.insert("k".to_string(), ParamValue::Text("a".to_string()));
+    let mut call = MacroAction::call("inner", 0);
+    call.args.insert("k".to_string(), ParamValue::Text("$outer".to_string()));
+    let mut outer = named("outer", "", vec![call]);
+    outer.params.insert("outer".to_string(), ParamValue::Text("f5".to_string()));
+
+    let config = config_of(vec![inner, outer]);
+    assert_eq!(keys(&config.expand(&config.macros[1]).unwrap()), vec![("f5", 0)]);
+}
+
+#[test]
+fn undeclared_params_and_arguments_are_reported() {
+    let mut bad_arg = MacroAction::call("target", 0);
+    bad_arg.args.insert("nope".to_string(), ParamValue::Number(1));
+    let mut bad_repeat = MacroAction::new("a", 0, 0);
+    bad_repeat.repeat = Some(ParamValue::Text("$text".to_string()));
+    let mut with_text = named("text", "", vec![bad_repeat]);
+    with_text.params.insert("text".to_string(), ParamValue::Text("many".to_string()));
+
+    let config = config_of(vec![
+        named("target", "", vec![MacroAction::new("$missing", 0, 0)]),
+        named("caller", "", vec![bad_arg]),
+        with_text,
+    ]);
+
+    let errors = config.check_macros();
+    assert_eq!(
+        errors,
+        vec![
+            ExpandError::UnknownParam { from: "1".to_string(), name: "missing".to_string() },
+            ExpandError::UnknownArg { from: "1".to_string(), target: "target".to_string(), name: "nope".to_string() },
+        ]
+    );
+    assert!(matches!(config.expand(&config.macros[2]), Err(ExpandError::InvalidRepeat { .. })));
+}
+
+#[test]
+fn dollar_escapes_and_braces() {
+    let lookup = |name: &str| (name == "x").then(|| "1".to_string());
+    assert_eq!(substitute("a$x-${x}b", lookup), Ok("a1-1b".to_string()));
+    assert_eq!(substitute("$$x $", lookup), Ok("$x $".to_string()));
+    assert_eq!(substitute("$y", lookup), Err("y".to_string()));
+}
+
+#[test]
+fn params_survive_save_and_load() {
+    let dir = temp_dir("params");
+    let path = dir.join("config.toml");
+
+    let mut action = MacroAction::call("spam", 5);
+    action.repeat = Some(ParamValue::Text("$n".to_string()));
+    action.args.insert("key".to_string(), ParamValue::Text("e".to_string()));
+    let mut m = named("main", "", vec![action]);
+    m.params.insert("n".to_string(), ParamValue::Number(2));
+    config_of(vec![m]).save(&path).unwrap();
+
+    let loaded = MacroConfig::load(&path).unwrap();
+    let action = &loaded.macros[0].actions[0];
+    assert_eq!(loaded.macros[0].params.get("n"), Some(&ParamValue::Number(2)));
+    assert_eq!(action.repeat, Some(ParamValue::Text("$n".to_string())));
+    assert_eq!(action.args.get("key"), Some(&ParamValue::Text("e".to_string())));
+}
+
+#[test]
+fn huge_repeat_is_refused() {
+    let mut spam = MacroAction::new("a", 0, 0);
+    spam.repeat = Some(ParamValue::Number(u64::MAX));
+    let mut nested = MacroAction::group(vec![MacroAction::new("b", 0, 0); 1000], 0);
+    nested.repeat = Some(ParamValue::Number(MAX_EXPANDED_ACTIONS as u64));
+    let config = config_of(vec![named("spam", "", vec![spam]), named("nested", "", vec![nested])]);
+
+    for m in &config.macros {
+        assert_eq!(config.expand(m).unwrap_err(), ExpandError::TooLong { from: m.display_name().to_string() });
+    }
+}
+
+#[test]
+fn empty_expansion_keeps_its_delay() {
+    let mut empty = MacroAction::call("empty", 40);
+    empty.repeat = Some(ParamValue::Number(1_000_000_000));
+    let config = config_of(vec![
+        named("empty", "", Vec::new()),
+        named("main", "", vec![MacroAction::new("a", 0, 5), empty, MacroAction::new("b", 0, 0)]),
+    ]);
+
+    // 비어 있는 호출도 반복마다 쉬고, 끝없이 돌지 않는다
+    let expanded = config.expand(&config.macros[1]).unwrap();
+    assert_eq!(keys(&expanded), vec![("a", 5 + 40 * 1_000_000_000), ("b", 0)]);
+}
+
+#[test]
+fn zero_repeat_keeps_its_delay() {
+    let mut skipped_key = MacroAction::new("x", 10, 30);
+    skipped_key.repeat = Some(ParamValue::Text("$count".to_string()));
+    let mut skipped_group = MacroAction::group(vec![MacroAction::new("y", 0, 0)], 20);
+    skipped_group.repeat = Some(ParamValue::Number(0));
+    let actions = vec![MacroAction::new("a", 0, 5), skipped_key, skipped_group, MacroAction::new("b", 0, 0)];
+    let mut main = named("main", "", actions);
+    main.params.insert("count".to_string(), ParamValue::Number(0));
+    let config = config_of(vec![main]);
+
+    let expanded = config.expand(&config.macros[0]).unwrap();
+    assert_eq!(keys(&expanded), vec![("a", 5 + 30 + 20), ("b", 0)]);
+}
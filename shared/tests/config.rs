@@ -0,0 +1,401 @@
+use shared::config::{backup_path, rotating_backup_path, CONFIG_VERSION, MAX_BACKUPS};
+use shared::MacroConfig;
+use std::fs;
+
+mod common;
+use common::temp_dir;
+
+const V0_CONFIG: &str = r#"toggle_key = "`"
+
+[[macros]]
+trigger = "1"
+mode = 2
+
+[[macros.actions]]
+key = "a"
+hold_ms = 30
+delay_ms = 20
+"#;
+
+#[test]
+fn unversioned_config_is_migrated_and_backed_up() {
+    let dir = temp_dir("migrate-v0");
+    let path = dir.join("config.toml");
+    fs::write(&path, V0_CONFIG).unwrap();
+
+    let config = MacroConfig::load(&path).unwrap();
+
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(config.macros.len(), 1);
+    assert_eq!(config.macros[0].actions[0].hold_ms, 30);
+    assert_eq!(fs::read_to_string(backup_path(&path, 0)).unwrap(), V0_CONFIG);
+
+    let rewritten: toml::Table = fs::read_to_string(&path).unwrap().parse().unwrap();
+    assert_eq!(rewritten["version"].as_integer(), Some(i64::from(CONFIG_VERSION)));
+}
+
+#[test]
+fn current_config_is_not_rewritten() {
+    let dir = temp_dir("migrate-current");
+    let path = dir.join("config.toml");
+    let content = format!("version = {}\n{}", CONFIG_VERSION, V0_CONFIG);
+    fs::write(&path, &content).unwrap();
+
+    MacroConfig::load(&path).unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    assert!(!backup_path(&path, CONFIG_VERSION).exists());
+}
+
+#[test]
+fn newer_config_is_refused() {
+    let dir = temp_dir("migrate-newer");
+    let path = dir.join("config.toml");
+    let content = format!("version = {}\n{}", CONFIG_VERSION + 1, V0_CONFIG);
+    fs::write(&path, &content).unwrap();
+
+    let err = MacroConfig::load(&path).unwrap_err();
+
+    assert!(err.to_string().contains(&format!("버전 {}", CONFIG_VERSION + 1)), "{}", err);
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+}
+
+#[test]
+fn saved_config_records_current_version() {
+    let dir = temp_dir("save-version");
+    let path = dir.join("config.toml");
+
+    MacroConfig::default().save(&path).unwrap();
+
+    assert_eq!(MacroConfig::load(&path).unwrap().version, CONFIG_VERSION);
+}
+
+const ANNOTATED_CONFIG: &str = r#"# KeyM 설정 (직접 작성)
+version = 2
+toggle_key = "`"   # 토글은 물결표 키
+
+# 사냥 매크로
+[[macros]]
+id = "hunt"
+mode = 2
+trigger = "1"
+
+[[macros.actions]]
+# 스킬 시전
+delay_ms = 20    # 쿨다운 여유
+key = "a"
+hold_ms = 30
+
+[[macros.actions]]
+key = "b"
+hold_ms = 10
+delay_ms = 0
+
+# 두 번째 매크로는 비워 둠
+[[macros]]
+id = "empty"
+trigger = "2"
+mode = 0
+actions = []
+"#;
+
+#[test]
+fn save_without_changes_keeps_file_identical() {
+    let dir = temp_dir("preserve-identity");
+    let path = dir.join("config.toml");
+    fs::write(&path, ANNOTATED_CONFIG).unwrap();
+
+    MacroConfig::load(&path).unwrap().save(&path).unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), ANNOTATED_CONFIG);
+}
+
+#[test]
+fn editor_changes_keep_comments_and_order() {
+    let dir = temp_dir("preserve-edit");
+    let path = dir.join("config.toml");
+    fs::write(&path, ANNOTATED_CONFIG).unwrap();
+
+    // Editor에서 하는 것과 같은 수정: 값 변경, 액션 추가, 액션 삭제
+    let mut config = MacroConfig::load(&path).unwrap();
+    config.macros[0].actions[0].hold_ms = 45;
+    config.macros[0].actions.remove(1);
+    config.macros[1].trigger = "3".to_string();
+    config.macros[1].actions.push(shared::MacroAction::new("space", 5, 5));
+    config.save(&path).unwrap();
+
+    let saved = fs::read_to_string(&path).unwrap();
+    for comment in [
+        "# KeyM 설정 (직접 작성)",
+        "toggle_key = \"`\"   # 토글은 물결표 키",
+        "# 사냥 매크로",
+        "# 스킬 시전",
+        "delay_ms = 20    # 쿨다운 여유",
+        "# 두 번째 매크로는 비워 둠",
+    ] {
+        assert!(saved.contains(comment), "missing {:?} in\n{}", comment, saved);
+    }
+
+    // 손으로 정한 키 순서 유지
+    let delay = saved.find("delay_ms = 20").unwrap();
+    let key = saved.find("key = \"a\"").unwrap();
+    let hold = saved.find("hold_ms = 45").unwrap();
+    assert!(delay < key && key < hold, "{}", saved);
+    assert!(!saved.contains("key = \"b\""));
+    assert!(saved.contains("trigger = \"3\""));
+    assert!(saved.contains("\n[[macros.actions]]\nkey = \"space\""), "{}", saved);
+
+    let reloaded = MacroConfig::load(&path).unwrap();
+    assert_eq!(reloaded.macros[0].actions.len(), 1);
+    assert_eq!(reloaded.macros[1].actions[0].key, "space");
+}
+
+const THREE_MACROS: &str = r#"version = 2
+toggle_key = "`"
+
+# 첫째
+[[macros]]
+id = "one"
+trigger = "1"
+enabled = true   # 일부러 적어 둠
+
+[[macros.actions]]
+key = "a"
+hold_ms = 30
+delay_ms = 0
+
+# 둘째
+[[macros]]
+id = "two"
+trigger = "2"
+
+[[macros.actions]]
+key = "b"
+hold_ms = 30
+delay_ms = 0
+
+# 셋째
+[[macros]]
+id = "three"
+trigger = "3"
+"#;
+
+#[test]
+fn deleted_macro_takes_its_comments_along() {
+    let dir = temp_dir("preserve-delete");
+    let path = dir.join("config.toml");
+    fs::write(&path, THREE_MACROS).unwrap();
+
+    let mut config = MacroConfig::load(&path).unwrap();
+    config.macros.remove(0);
+    config.save(&path).unwrap();
+
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(!saved.contains("# 첫째") && !saved.contains("key = \"a\""), "{}", saved);
+    let two = saved.find("# 둘째\n[[macros]]\nid = \"two\"").expect(&saved);
+    let three = saved.find("# 셋째\n[[macros]]\nid = \"three\"").expect(&saved);
+    assert!(two < three);
+}
+
+#[test]
+fn reordered_macros_keep_their_comments() {
+    let dir = temp_dir("preserve-reorder");
+    let path = dir.join("config.toml");
+    fs::write(&path, THREE_MACROS).unwrap();
+
+    let mut config = MacroConfig::load(&path).unwrap();
+    config.macros.swap(0, 2);
+    config.save(&path).unwrap();
+
+    let saved = fs::read_to_string(&path).unwrap();
+    let three = saved.find("# 셋째\n[[macros]]\nid = \"three\"").expect(&saved);
+    let two = saved.find("# 둘째\n[[macros]]\nid = \"two\"").expect(&saved);
+    let one = saved.find("# 첫째\n[[macros]]\nid = \"one\"").expect(&saved);
+    let one_action = saved.find("key = \"a\"").unwrap();
+    assert!(three < two && two < one && one < one_action, "{}", saved);
+    // 손으로 적은 기본값은 지우지 않는다
+    assert!(saved.contains("enabled = true   # 일부러 적어 둠"), "{}", saved);
+
+    let ids: Vec<String> = MacroConfig::load(&path).unwrap().macros.into_iter().map(|m| m.id).collect();
+    assert_eq!(ids, ["three", "two", "one"]);
+}
+
+#[test]
+fn macros_without_ids_are_matched_by_trigger() {
+    let dir = temp_dir("preserve-trigger");
+    let path = dir.join("config.toml");
+    fs::write(&path, THREE_MACROS.replace("id = \"one\"\n", "").replace("id = \"two\"\n", "")).unwrap();
+
+    let mut config = MacroConfig::load(&path).unwrap();
+    config.macros.remove(0);
+    config.save(&path).unwrap();
+
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(!saved.contains("# 첫째"), "{}", saved);
+    assert!(saved.contains("# 둘째\n[[macros]]\ntrigger = \"2\""), "{}", saved);
+}
+
+#[test]
+fn macro_without_id_keeps_comments_when_trigger_changes() {
+    let dir = temp_dir("preserve-no-id");
+    let path = dir.join("config.toml");
+    let content = THREE_MACROS.replace("id = \"two\"\n", "");
+    fs::write(&path, &content).unwrap();
+
+    let mut config = MacroConfig::load(&path).unwrap();
+    config.macros[1].trigger = "f2".to_string();
+    config.save(&path).unwrap();
+
+    // 읽을 때 채워진 id와 기본값은 적지 않는다
+    let saved = fs::read_to_string(&path).unwrap();
+    assert_eq!(saved, content.replace("trigger = \"2\"", "trigger = \"f2\""));
+}
+
+#[test]
+fn migration_keeps_comments() {
+    let dir = temp_dir("preserve-migrate");
+    let path = dir.join("config.toml");
+    let content = format!("# 내 설정\n{}", V0_CONFIG);
+    fs::write(&path, &content).unwrap();
+
+    MacroConfig::load(&path).unwrap();
+
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.starts_with("# 내 설정\n"), "{}", saved);
+    assert!(saved.contains(&format!("version = {}", CONFIG_VERSION)));
+}
+
+fn config_with_trigger(trigger: &str) -> MacroConfig {
+    let mut config: MacroConfig = toml::from_str(V0_CONFIG).unwrap();
+    config.macros[0].trigger = trigger.to_string();
+    config
+}
+
+#[test]
+fn saves_rotate_previous_versions() {
+    let dir = temp_dir("backup-rotate");
+    let path = dir.join("config.toml");
+
+    for i in 0..(MAX_BACKUPS + 3) {
+        config_with_trigger(&i.to_string()).save(&path).unwrap();
+    }
+
+    let backups = MacroConfig::backups(&path).unwrap();
+    assert_eq!(backups.len(), MAX_BACKUPS);
+    assert_eq!(backups[0].index, 1);
+    let newest: MacroConfig = toml::from_str(&fs::read_to_string(&backups[0].path).unwrap()).unwrap();
+    assert_eq!(newest.macros[0].trigger, (MAX_BACKUPS + 1).to_string());
+    assert!(!rotating_backup_path(&path, MAX_BACKUPS + 1).exists());
+
+    // 임시 파일이 남지 않는다
+    let leftovers: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty());
+}
+
+#[test]
+fn unchanged_save_does_not_rotate() {
+    let dir = temp_dir("backup-unchanged");
+    let path = dir.join("config.toml");
+    let config = config_with_trigger("1");
+
+    config.save(&path).unwrap();
+    config.save(&path).unwrap();
+
+    assert!(MacroConfig::backups(&path).unwrap().is_empty());
+}
+
+#[test]
+fn restore_brings_back_backup_and_keeps_current() {
+    let dir = temp_dir("backup-restore");
+    let path = dir.join("config.toml");
+    config_with_trigger("1").save(&path).unwrap();
+    config_with_trigger("2").save(&path).unwrap();
+
+    let restored = MacroConfig::restore_backup(&path, 1).unwrap();
+
+    assert_eq!(restored.macros[0].trigger, "1");
+    assert_eq!(MacroConfig::load(&path).unwrap().macros[0].trigger, "1");
+    // 되돌리기 직전 내용("2")이 새 백업이 된다
+    let undo: MacroConfig = toml::from_str(&fs::read_to_string(rotating_backup_path(&path, 1)).unwrap()).unwrap();
+    assert_eq!(undo.macros[0].trigger, "2");
+}
+
+#[test]
+fn broken_backup_is_not_restored() {
+    let dir = temp_dir("backup-broken");
+    let path = dir.join("config.toml");
+    config_with_trigger("1").save(&path).unwrap();
+    fs::write(rotating_backup_path(&path, 1), "macros = 3").unwrap();
+
+    assert!(MacroConfig::restore_backup(&path, 1).is_err());
+    assert_eq!(MacroConfig::load(&path).unwrap().macros[0].trigger, "1");
+}
+
+#[test]
+fn migration_assigns_stable_macro_ids() {
+    let dir = temp_dir("migrate-ids");
+    let path = dir.join("config.toml");
+    fs::write(&path, format!("version = 1\n{}\n[[macros]]\ntrigger = \"2\"\nactions = []\n", V0_CONFIG)).unwrap();
+
+    let config = MacroConfig::load(&path).unwrap();
+    let ids: Vec<&str> = config.macros.iter().map(|m| m.id.as_str()).collect();
+    assert!(ids.iter().all(|id| id.starts_with('m') && !id.starts_with("macro-")), "{:?}", ids);
+    assert_ne!(ids[0], ids[1]);
+
+    // 다시 읽어도 같은 id
+    let again = MacroConfig::load(&path).unwrap();
+    assert_eq!(again.macros[1].id, ids[1]);
+    assert!(backup_path(&path, 1).exists());
+}
+
+#[test]
+fn missing_and_duplicate_ids_are_filled() {
+    let content = format!(
+        "version = {}\n\n[[macros]]\nid = \"macro-1\"\ntrigger = \"1\"\nactions = []\n\n\
+         [[macros]]\ntrigger = \"2\"\nactions = []\n\n\
+         [[macros]]\nid = \"macro-1\"\ntrigger = \"3\"\nactions = []\n",
+        CONFIG_VERSION
+    );
+
+    let config = MacroConfig::parse(&content).unwrap();
+
+    let ids: Vec<&str> = config.macros.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, vec!["macro-1", "macro-2", "macro-3"]);
+}
+
+#[test]
+fn disabled_macros_are_not_found_by_trigger() {
+    let content = format!(
+        "version = {}\n\n[[macros]]\nid = \"off\"\ntrigger = \"1\"\nenabled = false\nactions = []\n\n\
+         [[macros]]\nid = \"on\"\nname = \"사냥\"\ntags = [\"rpg\"]\ntrigger = \"2\"\nactions = []\n",
+        CONFIG_VERSION
+    );
+
+    let config = MacroConfig::parse(&content).unwrap();
+
+    assert!(config.get_macro("1").is_none());
+    assert!(config.get_macro_by_id("off").is_some());
+    let on = config.get_macro("2").unwrap();
+    assert!(on.enabled);
+    assert_eq!(on.display_name(), "사냥");
+    assert_eq!(on.tags, vec!["rpg".to_string()]);
+}
+
+#[test]
+fn chain_triggers_is_off_unless_set() {
+    let config = MacroConfig::parse(V0_CONFIG).unwrap();
+    assert!(!config.chain_triggers);
+
+    let dir = temp_dir("chain-triggers");
+    let path = dir.join("config.toml");
+    config.save(&path).unwrap();
+    assert!(!fs::read_to_string(&path).unwrap().contains("chain_triggers"));
+
+    let chained = MacroConfig { chain_triggers: true, ..config };
+    chained.save(&path).unwrap();
+    assert!(MacroConfig::load(&path).unwrap().chain_triggers);
+}
@@ -0,0 +1,130 @@
+use shared::config::{ConflictKind, Press};
+use shared::{MacroAction, MacroConfig};
+
+mod common;
+use common::{tap, with_id};
+
+fn kinds(config: &MacroConfig) -> Vec<(ConflictKind, Vec<String>)> {
+    config.trigger_conflicts().into_iter().map(|c| (c.kind, c.macro_ids)).collect()
+}
+
+fn self_triggers(config: &MacroConfig) -> Vec<String> {
+    config
+        .trigger_conflicts()
+        .into_iter()
+        .filter(|c| c.kind == ConflictKind::SelfTrigger)
+        .map(|c| c.macro_ids[0].clone())
+        .collect()
+}
+
+#[test]
+fn duplicates_and_toggle_key() {
+    let mut disabled = with_id("off", "1", vec![tap("c")]);
+    disabled.enabled = false;
+    let config = MacroConfig {
+        toggle_key: "`".to_string(),
+        macros: vec![
+            with_id("a", "shift+ctrl+1", vec![tap("a")]),
+            disabled,
+            with_id("b", "ctrl + shift+1", vec![tap("b")]),
+            with_id("c", "`", vec![tap("c")]),
+        ],
+        ..MacroConfig::default()
+    };
+
+    assert_eq!(
+        kinds(&config),
+        vec![
+            (ConflictKind::Duplicate, vec!["b".to_string(), "a".to_string()]),
+            (ConflictKind::ToggleKey, vec!["c".to_string()]),
+        ]
+    );
+    let message = config.trigger_conflicts()[0].to_string();
+    assert!(message.contains("ctrl + shift+1"), "{}", message);
+}
+
+#[test]
+fn bare_modifier_shadows_chords() {
+    let config = MacroConfig {
+        toggle_key: "alt".to_string(),
+        macros: vec![
+            with_id("chord", "ctrl+1", vec![tap("a")]),
+            with_id("plain", "1", vec![tap("b")]),
+            with_id("ctrl", "ctrl", vec![tap("c")]),
+            with_id("alt", "alt+2", vec![tap("d")]),
+        ],
+        ..MacroConfig::default()
+    };
+
+    let conflicts = config.trigger_conflicts();
+    assert_eq!(conflicts.len(), 2, "{:?}", conflicts);
+    assert_eq!(conflicts[0].kind, ConflictKind::Shadowed);
+    assert_eq!(conflicts[0].macro_ids, ["chord", "ctrl"]);
+    // 토글 키가 가로채는 경우
+    assert_eq!(conflicts[1].macro_ids, ["alt"]);
+    assert!(conflicts[1].to_string().contains("토글 키"), "{}", conflicts[1]);
+}
+
+#[test]
+fn macro_pressing_its_own_trigger() {
+    let config = MacroConfig {
+        macros: vec![
+            with_id("loop", "e", vec![tap("a"), tap("e")]),
+            // ctrl을 누른 채 1을 누르면 자기 트리거
+            with_id(
+                "chord",
+                "ctrl+1",
+                vec![MacroAction::press("ctrl", Press::Down, 0), tap("1"), MacroAction::press("ctrl", Press::Up, 0)],
+            ),
+            // ctrl 없이 1만 누르면 괜찮다
+            with_id("safe", "ctrl+2", vec![tap("2")]),
+            // call로 불린 매크로도 펼쳐서 본다
+            with_id("caller", "f", vec![MacroAction::call("inner", 0)]),
+            with_id("inner", "f9", vec![tap("f")]),
+            // 수정 키 없이 적은 트리거는 shift를 누른 채로도 실행된다
+            with_id(
+                "plain",
+                "g",
+                vec![MacroAction::press("shift", Press::Down, 0), tap("g"), MacroAction::press("shift", Press::Up, 0)],
+            ),
+            // 같은 조합의 트리거가 따로 있으면 그쪽이 실행된다
+            with_id(
+                "taken",
+                "h",
+                vec![MacroAction::press("shift", Press::Down, 0), tap("h"), MacroAction::press("shift", Press::Up, 0)],
+            ),
+            with_id("shift-h", "shift+h", vec![tap("x")]),
+        ],
+        chain_triggers: true,
+        ..MacroConfig::default()
+    };
+
+    assert_eq!(self_triggers(&config), ["loop", "chord", "caller", "plain"]);
+}
+
+#[test]
+fn self_trigger_needs_chain_triggers() {
+    let config = MacroConfig { macros: vec![with_id("loop", "e", vec![tap("e")])], ..MacroConfig::default() };
+    assert!(self_triggers(&config).is_empty());
+}
+
+#[test]
+fn plain_triggers_are_matched_with_modifiers_held() {
+    let config = MacroConfig {
+        toggle_key: "f8".to_string(),
+        macros: vec![
+            with_id("toggle", "shift+f8", vec![tap("a")]),
+            with_id("chord", "ctrl+shift+1", vec![tap("b")]),
+            with_id("ctrl", "shift+ctrl", vec![tap("c")]),
+        ],
+        ..MacroConfig::default()
+    };
+
+    assert_eq!(
+        kinds(&config),
+        vec![
+            (ConflictKind::ToggleKey, vec!["toggle".to_string()]),
+            (ConflictKind::Shadowed, vec!["chord".to_string(), "ctrl".to_string()]),
+        ]
+    );
+}
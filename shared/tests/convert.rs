@@ -0,0 +1,220 @@
+use shared::config::{ParamValue, Passthrough, Press};
+use shared::convert::ahk;
+use shared::convert::shell::{self, Tool};
+use shared::{MacroAction, MacroConfig};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// KEYM_BLESS=1 이면 기대 출력을 새로 쓴다
+fn check_golden(dir: &str, extension: &str, convert: impl Fn(&MacroConfig) -> String) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(dir);
+    let mut inputs: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "toml"))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "{}에 입력 파일이 없습니다", dir.display());
+
+    for input in inputs {
+        let config = MacroConfig::parse(&fs::read_to_string(&input).unwrap()).unwrap();
+        let actual = convert(&config);
+        let expected_path = input.with_extension(extension);
+
+        if std::env::var_os("KEYM_BLESS").is_some() {
+            fs::write(&expected_path, &actual).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&expected_path)
+            .unwrap_or_else(|_| panic!("{}가 없습니다 (KEYM_BLESS=1로 생성)", expected_path.display()));
+        assert_eq!(actual, expected, "{}", input.display());
+    }
+}
+
+#[test]
+fn ahk_golden_files() {
+    check_golden("ahk", "ahk", ahk::export);
+}
+
+#[test]
+fn shell_golden_files() {
+    check_golden("shell", "xdotool.sh", |config| shell::export(config, Tool::Xdotool));
+    check_golden("shell", "ydotool.sh", |config| shell::export(config, Tool::Ydotool));
+}
+
+#[test]
+fn shell_key_names() {
+    assert_eq!(shell::keysym("enter"), Some("Return"));
+    assert_eq!(shell::keysym(";"), Some("semicolon"));
+    assert_eq!(shell::keysym("q"), Some("q"));
+    assert_eq!(shell::keysym("mouse4"), None);
+    // 확장 키는 스캔코드와 이벤트 코드가 다르다
+    assert_eq!(shell::evdev_code("a"), Some(30));
+    assert_eq!(shell::evdev_code("f12"), Some(88));
+    assert_eq!(shell::evdev_code("up"), Some(103));
+    assert_eq!(shell::evdev_code("numenter"), Some(96));
+}
+
+#[test]
+fn shell_single_macro() {
+    let config = MacroConfig::parse("[[macros]]\ntrigger = \"1\"\nscript = \"tap a 30ms; wait 100ms\"\n").unwrap();
+    let script = shell::export_macro(&config, &config.macros[0], Tool::Xdotool).unwrap();
+    assert!(script.starts_with("#!/bin/sh\n"), "{}", script);
+    assert!(script.ends_with("xdotool keydown a\nsleep 0.03\nxdotool keyup a\nsleep 0.1\n"), "{}", script);
+}
+
+#[test]
+fn shell_function_names_do_not_collide() {
+    let config = MacroConfig::parse(
+        "version = 2\n\n[[macros]]\nid = \"macro-1\"\ntrigger = \"1\"\nscript = \"tap a\"\n\n[[macros]]\nid = \"macro_1\"\ntrigger = \"2\"\nscript = \"tap b\"\n",
+    )
+    .unwrap();
+    let script = shell::export(&config, Tool::Xdotool);
+    assert!(script.contains("\nmacro_macro_1() {\n"), "{}", script);
+    assert!(script.contains("\nmacro_macro_1_2() {\n"), "{}", script);
+    assert!(script.contains("'macro_1'|'2') macro_macro_1_2 ;;"), "{}", script);
+}
+
+#[test]
+fn ahk_key_names() {
+    assert_eq!(ahk::key_name("esc"), Some("Escape"));
+    assert_eq!(ahk::key_name("num+"), Some("NumpadAdd"));
+    assert_eq!(ahk::key_name("q"), Some("q"));
+    assert_eq!(ahk::key_name("`"), Some("`"));
+    assert_eq!(ahk::key_name("mouse4"), None);
+    assert_eq!(ahk::key_name("A"), None);
+}
+
+#[test]
+fn ahk_import_subset() {
+    let source = r#"#Requires AutoHotkey v2.0
+SendMode "Input"
+
+$F8::Suspend -1
+
+; 사냥
+$1:: {
+    Send "{a down}"
+    Sleep 30
+    Send "{a up}"
+    Sleep 100
+    Loop 3 {
+        Send "e"
+        Sleep 10
+    }
+}
+
+~^+2::Send "^c"
+
+3::
+Send, Hi{Enter 2}
+return
+"#;
+
+    let imported = ahk::import(source);
+    assert!(imported.unsupported.is_empty(), "{:?}", imported.unsupported);
+    assert_eq!(imported.toggle_key.as_deref(), Some("f8"));
+    assert_eq!(imported.macros.len(), 3);
+
+    let hunt = &imported.macros[0];
+    assert_eq!((hunt.trigger.as_str(), hunt.name.as_str()), ("1", "사냥"));
+    assert_eq!(hunt.actions[0], MacroAction::new("a", 30, 100));
+    assert_eq!(hunt.actions[1].repeat, Some(ParamValue::Number(3)));
+    assert_eq!(hunt.actions[1].group, vec![MacroAction::new("e", 0, 10)]);
+
+    let copy = &imported.macros[1];
+    assert_eq!(copy.trigger, "ctrl+shift+2");
+    assert_eq!(copy.passthrough, Passthrough::Before);
+    assert_eq!(hunt.passthrough, Passthrough::Off);
+    assert_eq!(
+        copy.actions,
+        vec![
+            MacroAction::press("ctrl", Press::Down, 0),
+            MacroAction::new("c", 0, 0),
+            MacroAction::press("ctrl", Press::Up, 0),
+        ]
+    );
+
+    let keys: Vec<&str> = imported.macros[2].actions.iter().map(|a| a.key.as_str()).collect();
+    assert_eq!(keys, ["shift", "h", "shift", "i", "enter"]);
+    assert_eq!(imported.macros[2].actions[4].repeat, Some(ParamValue::Number(2)));
+}
+
+#[test]
+fn ahk_import_keeps_huge_counts_as_one_action() {
+    let imported = ahk::import("1::Send \"{a 999999999}\"\n");
+    assert!(imported.unsupported.is_empty(), "{:?}", imported.unsupported);
+    assert_eq!(
+        imported.macros[0].actions,
+        [MacroAction { repeat: Some(ParamValue::Number(999_999_999)), ..MacroAction::new("a", 0, 0) }]
+    );
+}
+
+#[test]
+fn ahk_import_reports_unsupported_lines() {
+    let source = "#z::Send \"a\"
+1:: {
+    Send \"a\"
+    MsgBox \"hi\"
+    Loop {
+        Send \"b\"
+    }
+    Send \"{Volume_Up}\"
+}
+a & b::Send \"c\"
+Run \"notepad\"
+2::Send \"x\"
+";
+
+    let imported = ahk::import(source);
+    let lines: Vec<usize> = imported.unsupported.iter().map(|u| u.line).collect();
+    assert_eq!(lines, [1, 4, 5, 8, 10, 11], "{:?}", imported.unsupported);
+    assert_eq!(imported.unsupported[1].text, "MsgBox \"hi\"");
+
+    // 지원하지 않는 줄만 빠지고 나머지는 가져온다
+    let triggers: Vec<&str> = imported.macros.iter().map(|m| m.trigger.as_str()).collect();
+    assert_eq!(triggers, ["1", "2"]);
+    assert_eq!(imported.macros[0].actions, vec![MacroAction::new("a", 0, 0)]);
+}
+
+#[test]
+fn ahk_export_then_import_keeps_actions() {
+    let config = MacroConfig::parse(
+        r#"
+version = 2
+toggle_key = "f8"
+
+[[macros]]
+id = "a"
+trigger = "ctrl+1"
+script = "tap a 30ms; wait 100ms; hold shift { tap 1 }; tap esc; tap \";\" 5ms"
+
+[[macros]]
+id = "b"
+trigger = "`"
+script = "tap f5 10ms; wait 1s; tap num/"
+"#,
+    )
+    .unwrap();
+
+    let imported = ahk::import(&ahk::export(&config));
+    assert!(imported.unsupported.is_empty(), "{:?}", imported.unsupported);
+    assert_eq!(imported.toggle_key.as_deref(), Some("f8"));
+    for (original, imported) in config.macros.iter().zip(&imported.macros) {
+        assert_eq!(imported.trigger, original.trigger);
+        assert_eq!(imported.actions, original.actions, "{}", original.trigger);
+    }
+}
+
+#[test]
+fn ahk_export_with_passthrough_after_imports() {
+    let config = MacroConfig::parse(
+        "version = 2\n\n[[macros]]\nid = \"a\"\ntrigger = \"1\"\npassthrough = \"after\"\nscript = \"tap a\"\n",
+    )
+    .unwrap();
+
+    let imported = ahk::import(&ahk::export(&config));
+    assert!(imported.unsupported.is_empty(), "{:?}", imported.unsupported);
+    let keys: Vec<(&str, Press)> = imported.macros[0].actions.iter().map(|a| (a.key.as_str(), a.press)).collect();
+    assert_eq!(keys, [("a", Press::Tap), ("1", Press::Tap)]);
+}
@@ -0,0 +1,146 @@
+use shared::config::{ParamValue, Press};
+use shared::dsl;
+use shared::{Macro, MacroAction, MacroConfig};
+
+mod common;
+use common::temp_dir;
+
+fn expand(actions: Vec<MacroAction>) -> Vec<(String, Press, u64, u64)> {
+    let m = Macro { actions, ..Macro::new("1") };
+    let config = MacroConfig { macros: vec![m.clone()], ..MacroConfig::default() };
+    config
+        .expand(&m)
+        .unwrap()
+        .actions
+        .into_iter()
+        .map(|a| (a.key, a.press, a.hold_ms, a.delay_ms))
+        .collect()
+}
+
+fn tap(key: &str, hold_ms: u64, delay_ms: u64) -> (String, Press, u64, u64) {
+    (key.to_string(), Press::Tap, hold_ms, delay_ms)
+}
+
+#[test]
+fn example_from_request_parses() {
+    let actions = dsl::parse("tap a 30ms; wait 100ms; hold shift { tap 1 }; repeat 5 { tap e }").unwrap();
+
+    assert_eq!(actions[0], MacroAction::new("a", 30, 100));
+    assert_eq!(actions[1], MacroAction::press("shift", Press::Down, 0));
+    assert_eq!(actions[2], MacroAction::new("1", 0, 0));
+    assert_eq!(actions[3], MacroAction::press("shift", Press::Up, 0));
+    assert_eq!(actions[4].repeat, Some(ParamValue::Number(5)));
+    assert_eq!(actions.len(), 5);
+}
+
+#[test]
+fn printed_text_round_trips() {
+    for script in [
+        "tap a 30ms; wait 100ms; hold shift { tap 1 }; repeat 5 { tap e }",
+        "hold ctrl { wait 20ms; tap c 10ms }; wait 1s",
+        "repeat 3 { tap a; tap b 15ms; wait 40ms }; wait 200ms",
+        "repeat $count { call login level=3 pin=\"0042\" user=$name }",
+        "tap \";\"; tap \"{\"; tap \\; hold alt { tap tab }",
+        "down alt; tap tab",
+    ] {
+        let actions = dsl::parse(script).unwrap();
+        assert_eq!(dsl::print(&actions), script);
+        assert_eq!(dsl::parse(&dsl::print(&actions)).unwrap(), actions);
+    }
+
+    // 짝이 맞는 down/up은 hold 블록으로 내보낸다
+    let actions = dsl::parse("down alt; tap tab; up alt").unwrap();
+    assert_eq!(dsl::print(&actions), "hold alt { tap tab }");
+}
+
+#[test]
+fn wait_after_repeat_is_not_repeated() {
+    let per_repeat = dsl::parse("repeat 2 { tap e; wait 10ms }").unwrap();
+    let once = dsl::parse("repeat 2 { tap e }; wait 10ms").unwrap();
+
+    assert_eq!(expand(per_repeat), vec![tap("e", 0, 10), tap("e", 0, 10)]);
+    assert_eq!(expand(once), vec![tap("e", 0, 0), tap("e", 0, 10)]);
+}
+
+#[test]
+fn model_prints_to_equivalent_script() {
+    // TOML로 작성한 액션을 문법으로 내보내고 다시 읽어도 같은 순서로 실행된다
+    let mut spam = MacroAction::new("e", 20, 30);
+    spam.repeat = Some(ParamValue::Number(2));
+    let actions = vec![
+        MacroAction::new("a", 50, 0),
+        MacroAction::group(vec![MacroAction::new("b", 0, 5), spam], 100),
+        MacroAction::press("shift", Press::Down, 15),
+        MacroAction::new("1", 10, 0),
+        MacroAction::press("shift", Press::Up, 0),
+    ];
+
+    let script = dsl::print(&actions);
+    assert_eq!(expand(dsl::parse(&script).unwrap()), expand(actions));
+}
+
+#[test]
+fn comments_and_newlines() {
+    let actions = dsl::parse(
+        "# 로그인
+         tap enter 20ms   # 확인
+         wait 2s
+
+         tap esc",
+    )
+    .unwrap();
+    assert_eq!(actions, vec![MacroAction::new("enter", 20, 2000), MacroAction::new("esc", 0, 0)]);
+}
+
+#[test]
+fn errors_point_at_the_problem() {
+    let error = dsl::parse("tap a\nwait soon").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 6));
+
+    let error = dsl::parse("wait 10ms").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 1));
+
+    for bad in ["jump a", "hold shift { tap a", "tap a }", "repeat x { tap a }", "repeat 2 {}", "tap a b", "tap \"a"] {
+        assert!(dsl::parse(bad).is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn inline_script_in_config() {
+    let dir = temp_dir("dsl");
+    let path = dir.join("config.toml");
+    std::fs::write(
+        &path,
+        "version = 2\n\n[[macros]]\nid = \"hunt\"\ntrigger = \"1\"\nscript = \"tap a 30ms; wait 100ms\"  # 사냥\n",
+    )
+    .unwrap();
+
+    let mut config = MacroConfig::load(&path).unwrap();
+    assert_eq!(config.macros[0].actions, vec![MacroAction::new("a", 30, 100)]);
+
+    // Editor에서 바꾼 액션은 script로 다시 저장된다
+    config.macros[0].actions.push(MacroAction::new("b", 10, 0));
+    config.save(&path).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("script = \"tap a 30ms; wait 100ms; tap b 10ms\"  # 사냥"), "{}", saved);
+    assert!(!saved.contains("[[macros.actions]]"), "{}", saved);
+
+    let error = MacroConfig::parse("[[macros]]\ntrigger = \"1\"\nscript = \"tap\"\n").unwrap_err();
+    assert!(error.to_string().contains("1줄 4열"), "{}", error);
+}
+
+#[test]
+fn unchanged_script_is_saved_as_written() {
+    let dir = temp_dir("dsl-keep");
+    let path = dir.join("config.toml");
+    let content = "version = 2\n\n[[macros]]\nid = \"hunt\"\ntrigger = \"1\"\nscript = \"\"\"\n# 스킬\ntap a 30ms\nwait 100ms  # 쿨다운\n\"\"\"\n\n[[macros]]\nid = \"other\"\ntrigger = \"2\"\nscript = \"tap b\"\n";
+    std::fs::write(&path, content).unwrap();
+
+    // 다른 매크로만 바꿔도 이 script는 그대로 남는다
+    let mut config = MacroConfig::load(&path).unwrap();
+    config.macros[1].actions.push(MacroAction::new("c", 0, 0));
+    config.save(&path).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("script = \"\"\"\n# 스킬\ntap a 30ms\nwait 100ms  # 쿨다운\n\"\"\"\n"), "{}", saved);
+    assert!(saved.contains("script = \"tap b; tap c\""), "{}", saved);
+}
@@ -0,0 +1,231 @@
+use shared::config::Press;
+use shared::executor::{Clock, ExecEvent, FakeClock, InputBackend};
+use shared::{Executor, Macro, MacroAction, TimingStats};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// 보낸 키를 기록하고, 보낼 때마다 시계를 latency 만큼 진행시키는 백엔드
+struct RecordingBackend<'a> {
+    clock: &'a FakeClock,
+    latency: Duration,
+    sent: RefCell<Vec<(String, bool, Duration)>>,
+    batches: RefCell<Vec<Vec<(String, bool)>>>,
+}
+
+impl<'a> RecordingBackend<'a> {
+    fn new(clock: &'a FakeClock, latency: Duration) -> Self {
+        Self {
+            clock,
+            latency,
+            sent: RefCell::new(Vec::new()),
+            batches: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl InputBackend for RecordingBackend<'_> {
+    fn send(&self, key: &str, is_keyup: bool) -> bool {
+        self.send_batch(&[(key, is_keyup)])
+    }
+
+    fn send_batch(&self, events: &[(&str, bool)]) -> bool {
+        if events.iter().any(|&(key, _)| key == "nope") {
+            return false;
+        }
+        self.clock.advance(self.latency);
+        let now = self.clock.now();
+        let mut sent = self.sent.borrow_mut();
+        sent.extend(events.iter().map(|&(key, up)| (key.to_string(), up, now)));
+        self.batches
+            .borrow_mut()
+            .push(events.iter().map(|&(key, up)| (key.to_string(), up)).collect());
+        true
+    }
+}
+
+fn action(key: &str, hold_ms: u64, delay_ms: u64) -> MacroAction {
+    MacroAction::new(key, hold_ms, delay_ms)
+}
+
+fn macro_of(actions: Vec<MacroAction>) -> Macro {
+    Macro { actions, ..Macro::new("1") }
+}
+
+#[test]
+fn perfect_clock_has_no_drift() {
+    let clock = FakeClock::new();
+    let backend = RecordingBackend::new(&clock, Duration::ZERO);
+    let cancel = AtomicBool::new(false);
+    let m = macro_of(vec![action("a", 30, 20), action("b", 10, 0)]);
+
+    let report = Executor::new(&clock, &backend, &cancel).run(&m, |_| {});
+
+    assert!(report.completed);
+    assert_eq!(report.done, 2);
+    assert_eq!(report.timing.events, 4);
+    assert_eq!(report.timing.max_drift_us, 0);
+    assert_eq!(report.timing.nominal_us, 60_000);
+    assert_eq!(report.timing.actual_us, 60_000);
+    assert_eq!(report.timing.overrun_us, 0);
+}
+
+#[test]
+fn send_latency_is_reported_as_drift() {
+    let clock = FakeClock::new();
+    let backend = RecordingBackend::new(&clock, Duration::from_micros(100));
+    let cancel = AtomicBool::new(false);
+    let m = macro_of(vec![action("a", 1, 0)]);
+
+    let mut drifts = Vec::new();
+    let report = Executor::new(&clock, &backend, &cancel).run(&m, |event| {
+        if let ExecEvent::KeySent { drift_us, .. } = event {
+            drifts.push(drift_us);
+        }
+    });
+
+    assert_eq!(drifts, vec![100, 100]);
+    assert_eq!(report.timing.mean_drift_us, 100);
+    assert_eq!(report.timing.max_drift_us, 100);
+    assert_eq!(report.timing.overrun_us, 100);
+}
+
+#[test]
+fn same_instant_events_are_sent_as_one_batch() {
+    let clock = FakeClock::new();
+    let backend = RecordingBackend::new(&clock, Duration::from_micros(100));
+    let cancel = AtomicBool::new(false);
+    let m = macro_of(vec![action("a", 0, 0), action("b", 0, 10), action("c", 5, 0)]);
+
+    let mut drifts = Vec::new();
+    Executor::new(&clock, &backend, &cancel).run(&m, |event| {
+        if let ExecEvent::KeySent { drift_us, .. } = event {
+            drifts.push(drift_us);
+        }
+    });
+
+    let batches = backend.batches.borrow();
+    let owned = |events: &[(&str, bool)]| -> Vec<(String, bool)> {
+        events.iter().map(|&(k, up)| (k.to_string(), up)).collect()
+    };
+    assert_eq!(batches.len(), 3);
+    assert_eq!(batches[0], owned(&[("a", false), ("a", true), ("b", false), ("b", true)]));
+    assert_eq!(batches[1], owned(&[("c", false)]));
+    assert_eq!(batches[2], owned(&[("c", true)]));
+    // 한 묶음은 지연을 한 번만 겪는다
+    assert_eq!(drifts, vec![100, 100, 100, 100, 100, 100]);
+}
+
+#[test]
+fn late_wakeups_do_not_accumulate() {
+    let clock = FakeClock::with_overshoot(Duration::from_micros(50));
+    let backend = RecordingBackend::new(&clock, Duration::ZERO);
+    let cancel = AtomicBool::new(false);
+    let m = macro_of((0..10).map(|_| action("a", 5, 5)).collect());
+
+    let report = Executor::new(&clock, &backend, &cancel).run(&m, |_| {});
+
+    // 매번 50us 늦게 깨어나도 다음 이벤트는 절대 시각 기준이라 오차가 쌓이지 않는다
+    assert_eq!(report.timing.nominal_us, 100_000);
+    assert_eq!(report.timing.max_drift_us, 50);
+    assert_eq!(report.timing.overrun_us, 50);
+}
+
+#[test]
+fn long_macro_ends_near_nominal_duration() {
+    let clock = FakeClock::with_overshoot(Duration::from_micros(80));
+    let backend = RecordingBackend::new(&clock, Duration::from_micros(150));
+    let cancel = AtomicBool::new(false);
+    let m = macro_of((0..2000).map(|_| action("a", 3, 2)).collect());
+
+    let report = Executor::new(&clock, &backend, &cancel).run(&m, |_| {});
+
+    assert!(report.completed);
+    assert_eq!(report.timing.events, 4000);
+    assert_eq!(report.timing.nominal_us, 10_000_000);
+    assert!(report.timing.overrun_us < 1_000, "overrun {}us", report.timing.overrun_us);
+    assert!(report.timing.max_drift_us < 1_000, "drift {}us", report.timing.max_drift_us);
+}
+
+#[test]
+fn cancel_releases_held_keys() {
+    let clock = FakeClock::new();
+    let backend = RecordingBackend::new(&clock, Duration::ZERO);
+    let cancel = AtomicBool::new(false);
+    let m = macro_of(vec![action("a", 100, 0), action("b", 10, 0)]);
+
+    let report = Executor::new(&clock, &backend, &cancel).run(&m, |event| {
+        if let ExecEvent::KeySent { keyup: false, .. } = event {
+            cancel.store(true, Ordering::SeqCst);
+        }
+    });
+
+    assert!(!report.completed);
+    assert_eq!(report.done, 0);
+    let sent: Vec<_> = backend.sent.borrow().iter().map(|(k, up, _)| (k.clone(), *up)).collect();
+    assert_eq!(sent, vec![("a".to_string(), false), ("a".to_string(), true)]);
+}
+
+#[test]
+fn unknown_keys_are_skipped_without_scheduling() {
+    let clock = FakeClock::new();
+    let backend = RecordingBackend::new(&clock, Duration::ZERO);
+    let cancel = AtomicBool::new(false);
+    let m = macro_of(vec![action("nope", 100, 100), action("a", 10, 10)]);
+
+    let mut unknown = Vec::new();
+    let report = Executor::new(&clock, &backend, &cancel).run(&m, |event| {
+        if let ExecEvent::UnknownKey { key } = event {
+            unknown.push(key.to_string());
+        }
+    });
+
+    assert_eq!(unknown, vec!["nope".to_string()]);
+    assert_eq!(report.done, 2);
+    assert_eq!(report.timing.nominal_us, 20_000);
+    assert_eq!(backend.sent.borrow().len(), 2);
+}
+
+#[test]
+fn press_only_actions_count_as_done() {
+    let clock = FakeClock::new();
+    let backend = RecordingBackend::new(&clock, Duration::ZERO);
+    let cancel = AtomicBool::new(false);
+    let m = macro_of(vec![MacroAction::press("shift", Press::Down, 10), action("a", 10, 10)]);
+
+    let mut progress = Vec::new();
+    let report = Executor::new(&clock, &backend, &cancel).run(&m, |event| {
+        if let ExecEvent::Progress { done, total } = event {
+            progress.push((done, total));
+        }
+    });
+
+    assert_eq!(progress, vec![(1, 2), (2, 2)]);
+    assert_eq!(report.done, 2);
+}
+
+#[test]
+fn cancelled_run_stops_before_sending() {
+    let clock = FakeClock::new();
+    let backend = RecordingBackend::new(&clock, Duration::ZERO);
+    let cancel = AtomicBool::new(true);
+    let m = macro_of(vec![action("a", 10, 10)]);
+
+    let report = Executor::new(&clock, &backend, &cancel).run(&m, |_| {});
+
+    assert!(!report.completed);
+    assert_eq!(report.done, 0);
+    assert!(backend.sent.borrow().is_empty());
+}
+
+#[test]
+fn p99_picks_the_tail_sample() {
+    let mut drifts: Vec<i64> = (1..=200).collect();
+    drifts.reverse();
+    let stats = TimingStats::from_samples(&drifts, Duration::ZERO, Duration::ZERO);
+
+    assert_eq!(stats.events, 200);
+    assert_eq!(stats.p99_drift_us, 198);
+    assert_eq!(stats.max_drift_us, 200);
+    assert_eq!(stats.mean_drift_us, 100);
+}
@@ -0,0 +1,146 @@
+use shared::gesture::{Decision, Gesture, GestureRecognizer};
+use shared::trigger::Chord;
+use shared::config::Passthrough;
+use shared::{Macro, MacroConfig};
+
+mod common;
+
+fn config_of(bindings: &[(&str, Gesture)]) -> MacroConfig {
+    common::config_of(bindings.iter().map(|&(trigger, gesture)| Macro { gesture, ..Macro::new(trigger) }).collect())
+}
+
+/// (시각, 키, 누름) 이벤트 열을 흘려 실행된 매크로를 (시각, 인덱스)로 모은다.
+/// 이벤트 사이에는 1ms마다 poll 한다
+fn run(config: &MacroConfig, events: &[(u64, &str, bool)], until: u64) -> Vec<(u64, usize)> {
+    let mut recognizer = GestureRecognizer::new(config);
+    let mut fired = Vec::new();
+    let mut events = events.iter().peekable();
+    for now in 0..=until {
+        while let Some(&&(at, key, down)) = events.peek().filter(|e| e.0 == now) {
+            let Decision { fire, .. } = if down {
+                recognizer.key_down(&Chord::parse(key), at)
+            } else {
+                recognizer.key_up(&Chord::parse(key).key, at)
+            };
+            fired.extend(fire.into_iter().map(|i| (now, i)));
+            events.next();
+        }
+        fired.extend(recognizer.poll(now).into_iter().map(|i| (now, i)));
+    }
+    fired
+}
+
+#[test]
+fn press_and_release() {
+    let config = config_of(&[("1", Gesture::Press), ("1", Gesture::Release)]);
+    // 자동 반복 입력마다 press가 다시 실행된다
+    let fired = run(&config, &[(0, "1", true), (30, "1", true), (50, "1", false)], 100);
+    assert_eq!(fired, [(0, 0), (30, 0), (50, 1)]);
+}
+
+#[test]
+fn tap_and_long_press_on_same_key() {
+    let config = config_of(&[("e", Gesture::Tap(200)), ("e", Gesture::LongPress(300))]);
+
+    assert_eq!(run(&config, &[(0, "e", true), (100, "e", false)], 500), [(100, 0)]);
+    // 길게 누르면 떼기 전에 실행되고, 뗄 때 tap은 없다
+    assert_eq!(run(&config, &[(0, "e", true), (400, "e", false)], 500), [(300, 1)]);
+    // 그 사이는 아무것도 아니다
+    assert!(run(&config, &[(0, "e", true), (250, "e", false)], 500).is_empty());
+}
+
+#[test]
+fn double_tap_delays_single_tap() {
+    let config = config_of(&[("q", Gesture::Tap(150)), ("q", Gesture::DoubleTap(200))]);
+
+    let double = run(&config, &[(0, "q", true), (50, "q", false), (150, "q", true), (200, "q", false)], 600);
+    assert_eq!(double, [(150, 1)]);
+
+    // 두 번째 입력이 없으면 기다린 뒤 tap
+    let single = run(&config, &[(0, "q", true), (50, "q", false)], 600);
+    assert_eq!(single, [(251, 0)]);
+
+    // 너무 늦은 두 번째 입력은 각각 tap
+    let slow = run(&config, &[(0, "q", true), (50, "q", false), (260, "q", true), (300, "q", false)], 800);
+    assert_eq!(slow, [(251, 0), (501, 0)]);
+}
+
+#[test]
+fn chords_and_unbound_keys() {
+    let config = config_of(&[("ctrl+1", Gesture::Release)]);
+    let mut recognizer = GestureRecognizer::new(&config);
+
+    let plain = recognizer.key_down(&Chord::parse("1"), 0);
+    assert_eq!(plain, Decision::default());
+    recognizer.key_up("1", 10);
+
+    // 수정 키를 먼저 떼도 누를 때의 조합으로 처리한다
+    let down = recognizer.key_down(&Chord::parse("ctrl+1"), 20);
+    assert!(down.consume && down.fire.is_empty());
+    assert_eq!(recognizer.key_up("1", 40), Decision { fire: vec![0], consume: true });
+}
+
+#[test]
+fn plain_trigger_fires_with_modifiers_held() {
+    let config = config_of(&[("1", Gesture::Press), ("2", Gesture::Press), ("shift+2", Gesture::Press)]);
+    let mut recognizer = GestureRecognizer::new(&config);
+
+    // 수정 키 없이 적은 트리거는 수정 키를 누른 채로도 실행된다
+    assert_eq!(recognizer.key_down(&Chord::parse("shift+1"), 0), Decision { fire: vec![0], consume: true });
+    assert_eq!(recognizer.key_up("1", 10), Decision { fire: vec![], consume: true });
+    // 같은 키의 조합 트리거가 있으면 그쪽이 이긴다
+    assert_eq!(recognizer.key_down(&Chord::parse("shift+2"), 20).fire, [2]);
+    recognizer.key_up("2", 30);
+    assert_eq!(recognizer.key_down(&Chord::parse("ctrl+2"), 40).fire, [1]);
+}
+
+#[test]
+fn keys_pass_through_while_a_macro_is_running() {
+    let config = config_of(&[("1", Gesture::Press), ("2", Gesture::Release)]);
+    let mut recognizer = GestureRecognizer::new(&config);
+
+    // 실행 전에 누른 키는 자동 반복과 떼기까지 삼킨다
+    assert!(recognizer.key_down(&Chord::parse("2"), 0).consume);
+    assert_eq!(recognizer.key_down_while_running(&Chord::parse("2")), Decision { fire: vec![], consume: true });
+
+    // 실행 중에 누른 키는 실행하지도, 삼키지도 않는다
+    assert_eq!(recognizer.key_down_while_running(&Chord::parse("1")), Decision::default());
+    assert_eq!(recognizer.key_up("1", 20), Decision::default());
+    assert_eq!(recognizer.key_up("2", 30), Decision { fire: vec![1], consume: true });
+
+    // 끝난 뒤에는 다시 트리거
+    assert_eq!(recognizer.key_down(&Chord::parse("1"), 40), Decision { fire: vec![0], consume: true });
+}
+
+#[test]
+fn gesture_round_trips_through_toml() {
+    let config = MacroConfig::parse(
+        "[[macros]]\ntrigger = \"1\"\ngesture = { double_tap = 250 }\n\n[[macros]]\ntrigger = \"2\"\ngesture = \"release\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.macros[0].gesture, Gesture::DoubleTap(250));
+    assert_eq!(config.macros[1].gesture, Gesture::Release);
+
+    let saved = toml::to_string(&config).unwrap();
+    assert!(saved.contains("gesture = \"release\""), "{}", saved);
+    assert_eq!(MacroConfig::parse(&saved).unwrap().macros[0].gesture, Gesture::DoubleTap(250));
+}
+
+#[test]
+fn passthrough_before_lets_trigger_through() {
+    let mut config = config_of(&[("1", Gesture::Press), ("2", Gesture::Release)]);
+    config.macros[0].passthrough = Passthrough::Before;
+    config.macros[1].passthrough = Passthrough::After;
+    let mut recognizer = GestureRecognizer::new(&config);
+
+    assert_eq!(recognizer.key_down(&Chord::parse("1"), 0), Decision { fire: vec![0], consume: false });
+    assert_eq!(recognizer.key_up("1", 10), Decision::default());
+
+    // after는 키를 삼켰다가 Runner가 매크로 뒤에 보낸다
+    assert!(recognizer.key_down(&Chord::parse("2"), 20).consume);
+    assert_eq!(recognizer.key_up("2", 30), Decision { fire: vec![1], consume: true });
+
+    let saved = toml::to_string(&config).unwrap();
+    assert!(saved.contains("passthrough = \"before\""), "{}", saved);
+    assert_eq!(saved.matches("passthrough").count(), 2);
+}
@@ -0,0 +1,31 @@
+#!/bin/sh
+# KeyM에서 내보낸 xdotool 스크립트
+# 사용법: keym.sh <매크로 id 또는 트리거>
+set -e
+
+# 사냥
+# 스킬 시전 후 줍기
+macro_hunt() {
+    xdotool keydown a
+    sleep 0.03
+    xdotool keyup a
+    sleep 0.02
+    xdotool key space
+    sleep 0.1
+}
+
+# f5
+macro_potion() {
+    xdotool keydown KP_1
+    sleep 0.01
+    xdotool keyup KP_1
+}
+
+case "${1:-}" in
+    'hunt'|'1') macro_hunt ;;
+    'potion'|'f5') macro_potion ;;
+    *)
+        echo "사용법: $0 <매크로 id 또는 트리거>" >&2
+        exit 1
+        ;;
+esac
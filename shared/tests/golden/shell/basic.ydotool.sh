@@ -0,0 +1,32 @@
+#!/bin/sh
+# KeyM에서 내보낸 ydotool 스크립트
+# ydotoold가 실행 중이어야 합니다
+# 사용법: keym.sh <매크로 id 또는 트리거>
+set -e
+
+# 사냥
+# 스킬 시전 후 줍기
+macro_hunt() {
+    ydotool key 30:1
+    sleep 0.03
+    ydotool key 30:0
+    sleep 0.02
+    ydotool key 57:1 57:0
+    sleep 0.1
+}
+
+# f5
+macro_potion() {
+    ydotool key 79:1
+    sleep 0.01
+    ydotool key 79:0
+}
+
+case "${1:-}" in
+    'hunt'|'1') macro_hunt ;;
+    'potion'|'f5') macro_potion ;;
+    *)
+        echo "사용법: $0 <매크로 id 또는 트리거>" >&2
+        exit 1
+        ;;
+esac
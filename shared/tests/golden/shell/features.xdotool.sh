@@ -0,0 +1,50 @@
+#!/bin/sh
+# KeyM에서 내보낸 xdotool 스크립트
+# 사용법: keym.sh <매크로 id 또는 트리거>
+set -e
+
+# 로그인
+# 비활성 매크로는 내보내지 않음
+
+# Bob's combo
+macro_combo_main() {
+    xdotool keydown Control_L
+    xdotool keydown c
+    sleep 0.01
+    xdotool keyup c
+    xdotool keyup Control_L
+    xdotool key Up
+    sleep 0.015
+    xdotool key Up
+    sleep 0.015
+    xdotool keydown Return
+    sleep 0.02
+    xdotool keyup Return
+    sleep 1.5
+    xdotool key KP_Enter
+}
+
+# '
+macro_duplicate() {
+    xdotool key b
+    # 알 수 없는 키 건너뜀: mouse4
+}
+
+# 4
+# 펼치기 실패: 4: 호출할 매크로를 찾을 수 없습니다: nowhere
+
+# 5
+macro_empty() {
+    # 알 수 없는 키 건너뜀: mouse4
+    :
+}
+
+case "${1:-}" in
+    'combo.main'|''\''') macro_combo_main ;;
+    'duplicate') macro_duplicate ;;
+    'empty'|'5') macro_empty ;;
+    *)
+        echo "사용법: $0 <매크로 id 또는 트리거>" >&2
+        exit 1
+        ;;
+esac
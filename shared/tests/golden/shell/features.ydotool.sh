@@ -0,0 +1,51 @@
+#!/bin/sh
+# KeyM에서 내보낸 ydotool 스크립트
+# ydotoold가 실행 중이어야 합니다
+# 사용법: keym.sh <매크로 id 또는 트리거>
+set -e
+
+# 로그인
+# 비활성 매크로는 내보내지 않음
+
+# Bob's combo
+macro_combo_main() {
+    ydotool key 29:1
+    ydotool key 46:1
+    sleep 0.01
+    ydotool key 46:0
+    ydotool key 29:0
+    ydotool key 103:1 103:0
+    sleep 0.015
+    ydotool key 103:1 103:0
+    sleep 0.015
+    ydotool key 28:1
+    sleep 0.02
+    ydotool key 28:0
+    sleep 1.5
+    ydotool key 96:1 96:0
+}
+
+# '
+macro_duplicate() {
+    ydotool key 48:1 48:0
+    # 알 수 없는 키 건너뜀: mouse4
+}
+
+# 4
+# 펼치기 실패: 4: 호출할 매크로를 찾을 수 없습니다: nowhere
+
+# 5
+macro_empty() {
+    # 알 수 없는 키 건너뜀: mouse4
+    :
+}
+
+case "${1:-}" in
+    'combo.main'|''\''') macro_combo_main ;;
+    'duplicate') macro_duplicate ;;
+    'empty'|'5') macro_empty ;;
+    *)
+        echo "사용법: $0 <매크로 id 또는 트리거>" >&2
+        exit 1
+        ;;
+esac
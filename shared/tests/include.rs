@@ -0,0 +1,171 @@
+use shared::MacroConfig;
+
+mod common;
+use common::{temp_dir, write};
+
+fn macro_toml(trigger: &str, key: &str) -> String {
+    format!("[[macros]]\ntrigger = \"{}\"\n\n[[macros.actions]]\nkey = \"{}\"\n\n", trigger, key)
+}
+
+fn keys(config: &MacroConfig) -> Vec<(String, String)> {
+    config
+        .macros
+        .iter()
+        .map(|m| (m.trigger.clone(), m.actions[0].key.clone()))
+        .collect()
+}
+
+#[test]
+fn personal_config_overrides_shared_library() {
+    let dir = temp_dir("include-layer");
+    let root = dir.join("config.toml");
+    write(&dir.join("team/common.toml"), &(macro_toml("1", "a") + &macro_toml("2", "b")));
+    write(&root, &format!("version = 1\ninclude = [\"team/common.toml\"]\ntoggle_key = \"f1\"\n\n{}", macro_toml("2", "z")));
+
+    let resolved = MacroConfig::load_resolved(&root).unwrap();
+
+    assert_eq!(
+        keys(&resolved.config),
+        vec![("1".to_string(), "a".to_string()), ("2".to_string(), "z".to_string())]
+    );
+    assert_eq!(resolved.config.toggle_key, "f1");
+    assert_eq!(resolved.files.len(), 2);
+    assert_eq!(resolved.conflicts.len(), 1);
+    assert_eq!(resolved.conflicts[0].trigger, "2");
+    assert!(resolved.conflicts[0].kept.ends_with("config.toml"));
+    assert!(resolved.conflicts[0].dropped.ends_with("team/common.toml"));
+
+    // 파일 자체를 읽으면 include한 매크로는 섞이지 않는다 (Editor 저장용)
+    assert_eq!(MacroConfig::load(&root).unwrap().macros.len(), 1);
+}
+
+#[test]
+fn later_includes_override_earlier_ones_and_nest_depth_first() {
+    let dir = temp_dir("include-order");
+    let root = dir.join("config.toml");
+    write(&dir.join("base.toml"), &macro_toml("1", "base"));
+    write(&dir.join("lib/a.toml"), &format!("include = [\"../base.toml\"]\n{}", macro_toml("1", "a")));
+    write(&dir.join("lib/b.toml"), &macro_toml("1", "b"));
+    write(&root, "version = 1\ninclude = [\"lib/a.toml\", \"lib/b.toml\", \"base.toml\"]\nmacros = []\n");
+
+    let resolved = MacroConfig::load_resolved(&root).unwrap();
+
+    // base → a → b 순서, base.toml은 한 번만 읽힌다
+    assert_eq!(keys(&resolved.config), vec![("1".to_string(), "b".to_string())]);
+    assert_eq!(resolved.files.len(), 4);
+    assert_eq!(resolved.conflicts.len(), 2);
+}
+
+#[test]
+fn duplicate_trigger_in_one_file_keeps_first() {
+    let dir = temp_dir("include-dup");
+    let root = dir.join("config.toml");
+    write(&root, &format!("version = 1\n{}{}", macro_toml("1", "first"), macro_toml("1", "second")));
+
+    let resolved = MacroConfig::load_resolved(&root).unwrap();
+
+    assert_eq!(keys(&resolved.config), vec![("1".to_string(), "first".to_string())]);
+    assert_eq!(resolved.conflicts.len(), 1);
+    assert_eq!(resolved.conflicts[0].kept, resolved.conflicts[0].dropped);
+}
+
+#[test]
+fn include_cycle_is_an_error() {
+    let dir = temp_dir("include-cycle");
+    let root = dir.join("config.toml");
+    write(&dir.join("a.toml"), "include = [\"b.toml\"]\nmacros = []\n");
+    write(&dir.join("b.toml"), "include = [\"a.toml\"]\nmacros = []\n");
+    write(&root, "version = 1\ninclude = [\"a.toml\"]\nmacros = []\n");
+
+    let err = MacroConfig::load_resolved(&root).unwrap_err();
+
+    assert!(err.to_string().contains("순환"), "{}", err);
+}
+
+#[test]
+fn missing_include_names_the_file() {
+    let dir = temp_dir("include-missing");
+    let root = dir.join("config.toml");
+    write(&root, "version = 1\ninclude = [\"nope.toml\"]\nmacros = []\n");
+
+    let err = MacroConfig::load_resolved(&root).unwrap_err();
+
+    assert!(err.to_string().contains("nope.toml"), "{}", err);
+}
+
+#[test]
+fn triggers_are_compared_normalized_and_macros_are_optional() {
+    let dir = temp_dir("include-normalize");
+    let root = dir.join("config.toml");
+    write(&dir.join("lib/all.toml"), "include = [\"keys.toml\"]\n");
+    write(&dir.join("lib/keys.toml"), &macro_toml("ctrl+shift+1", "lib"));
+    write(&root, &format!("version = 1\ninclude = [\"lib/all.toml\"]\n{}", macro_toml("shift+ctrl+1", "mine")));
+
+    let resolved = MacroConfig::load_resolved(&root).unwrap();
+
+    assert_eq!(keys(&resolved.config), vec![("shift+ctrl+1".to_string(), "mine".to_string())]);
+    assert_eq!(resolved.files.len(), 3);
+    assert_eq!(resolved.conflicts.len(), 1);
+    assert!(resolved.conflicts[0].dropped.ends_with("lib/keys.toml"));
+}
+
+#[test]
+fn included_macros_get_ids_after_merge() {
+    let dir = temp_dir("include-ids");
+    let root = dir.join("config.toml");
+    write(&dir.join("a.toml"), &macro_toml("f1", "a"));
+    write(&dir.join("b.toml"), &macro_toml("f2", "b"));
+    write(
+        &root,
+        "version = 2\ninclude = [\"a.toml\", \"b.toml\"]\n\n[[macros]]\nid = \"macro-1\"\ntrigger = \"1\"\n\n[[macros.actions]]\nkey = \"x\"\n\n[[macros]]\nid = \"uses\"\ntrigger = \"2\"\n\n[[macros.actions]]\ncall = \"macro-1\"\n",
+    );
+
+    let config = MacroConfig::load_resolved(&root).unwrap().config;
+
+    let ids: Vec<&str> = config.macros.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["macro-2", "macro-3", "macro-1", "uses"]);
+    let uses = config.macros.iter().find(|m| m.id == "uses").unwrap();
+    assert_eq!(config.expand(uses).unwrap().actions[0].key, "x");
+}
+
+#[test]
+fn duplicate_written_ids_across_files_are_an_error() {
+    let dir = temp_dir("include-dup-id");
+    let root = dir.join("config.toml");
+    write(&dir.join("lib.toml"), "[[macros]]\nid = \"same\"\ntrigger = \"f1\"\n");
+    write(&root, "version = 2\ninclude = [\"lib.toml\"]\n\n[[macros]]\nid = \"same\"\ntrigger = \"f2\"\n");
+
+    let err = MacroConfig::load_resolved(&root).unwrap_err();
+
+    assert!(err.to_string().starts_with("매크로 id가 겹칩니다: same"), "{}", err);
+}
+
+#[test]
+fn migrated_files_can_include_each_other() {
+    let dir = temp_dir("include-migrated");
+    let root = dir.join("config.toml");
+    let lib = dir.join("lib.toml");
+    write(&lib, &format!("version = 1\n{}", macro_toml("f1", "a")));
+    write(&root, &format!("version = 1\ninclude = [\"lib.toml\"]\n{}", macro_toml("f2", "b")));
+
+    // 둘 다 한 번씩 읽어 변환된 뒤에도 id가 겹치지 않는다
+    MacroConfig::load(&lib).unwrap();
+    let config = MacroConfig::load_resolved(&root).unwrap().config;
+
+    assert_eq!(keys(&config), [("f1".to_string(), "a".to_string()), ("f2".to_string(), "b".to_string())]);
+    assert_ne!(config.macros[0].id, config.macros[1].id);
+}
+
+#[test]
+fn ids_written_by_old_migrations_are_renewed_on_collision() {
+    let dir = temp_dir("include-old-ids");
+    let root = dir.join("config.toml");
+    write(&dir.join("lib.toml"), "version = 2\n\n[[macros]]\nid = \"macro-1\"\ntrigger = \"f1\"\n");
+    write(&root, "version = 2\ninclude = [\"lib.toml\"]\n\n[[macros]]\nid = \"macro-1\"\ntrigger = \"f2\"\n");
+
+    let config = MacroConfig::load_resolved(&root).unwrap().config;
+
+    // 최상위 파일의 매크로가 id를 지킨다
+    let ids: Vec<(&str, &str)> = config.macros.iter().map(|m| (m.trigger.as_str(), m.id.as_str())).collect();
+    assert_eq!(ids, [("f1", "macro-2"), ("f2", "macro-1")]);
+}
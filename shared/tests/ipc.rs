@@ -0,0 +1,92 @@
+use shared::ipc::{self, IpcEvent, IpcRequest};
+use shared::{Macro, MacroAction};
+use std::io::{self, BufReader, ErrorKind};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+/// 요청 하나를 받아 Started, Finished로 답하는 서버. 받은 요청을 돌려준다
+fn serve_once(listener: TcpListener, token: &'static str) -> thread::JoinHandle<std::io::Result<Option<IpcRequest>>> {
+    thread::spawn(move || {
+        let (stream, _) = listener.accept()?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        let request = ipc::read_request(&mut reader, token);
+        let reply = match &request {
+            Ok(Some(IpcRequest::Play { macro_item, .. })) => vec![
+                IpcEvent::Started { total: macro_item.actions.len() },
+                IpcEvent::Finished,
+            ],
+            Ok(_) => Vec::new(),
+            Err(e) => vec![IpcEvent::Error { message: e.to_string() }],
+        };
+        for event in reply {
+            ipc::write_message(&mut writer, &event)?;
+        }
+        request
+    })
+}
+
+fn events(stream: TcpStream) -> Vec<String> {
+    let mut reader = BufReader::new(stream);
+    let mut events = Vec::new();
+    while let Ok(Some(event)) = ipc::read_message::<_, IpcEvent>(&mut reader) {
+        events.push(format!("{:?}", event));
+    }
+    events
+}
+
+#[test]
+fn play_request_round_trips() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = serve_once(listener, "secret");
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let macro_item = Macro { actions: vec![MacroAction::new("a", 30, 100)], ..Macro::new("1") };
+    let request = IpcRequest::Play { macro_item: Box::new(macro_item), countdown_ms: 0 };
+    ipc::send_request(&mut stream, "secret".to_string(), request).unwrap();
+
+    assert_eq!(events(stream), ["Started { total: 1 }", "Finished"]);
+    match server.join().unwrap().unwrap() {
+        Some(IpcRequest::Play { macro_item, countdown_ms }) => {
+            assert_eq!(macro_item.actions, [MacroAction::new("a", 30, 100)]);
+            assert_eq!(countdown_ms, 0);
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn wrong_token_is_rejected() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = serve_once(listener, "secret");
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    ipc::send_request(&mut stream, "guess".to_string(), IpcRequest::Cancel).unwrap();
+
+    assert_eq!(events(stream).len(), 1);
+    let error = server.join().unwrap().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn tokens_are_random_and_stored() {
+    let first = ipc::new_token();
+    assert_eq!(first.len(), 32);
+    assert!(first.bytes().all(|b| b.is_ascii_hexdigit()));
+    assert_ne!(first, ipc::new_token());
+
+    let path = std::env::temp_dir().join(format!("keym-ipc-test-{}.token", std::process::id()));
+    ipc::write_token(&path, &first).unwrap();
+    assert_eq!(ipc::read_token(&path).unwrap(), first);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn endless_request_is_cut_off() {
+    // 줄바꿈 없이 끝없이 오는 입력도 MAX_REQUEST_BYTES까지만 읽는다
+    let mut reader = BufReader::new(io::repeat(b'x'));
+    let error = ipc::read_request(&mut reader, "secret").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
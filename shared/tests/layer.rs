@@ -0,0 +1,137 @@
+use shared::config::ConflictKind;
+use shared::gesture::GestureRecognizer;
+use shared::layer::{self, LayerError, LayerEvent, LayerState};
+use shared::remap::Remapper;
+use shared::trigger::Chord;
+use shared::MacroConfig;
+
+fn config() -> MacroConfig {
+    MacroConfig::parse(
+        r#"
+toggle_key = "f8"
+
+[remap]
+capslock = "esc"
+
+[[layers]]
+name = "nav"
+key = "space"
+
+[layers.remap]
+h = "left"
+
+[[layers]]
+name = "game"
+key = "f9"
+mode = "toggle"
+
+[[macros]]
+id = "base"
+trigger = "1"
+script = "tap a"
+
+[[macros]]
+id = "nav-1"
+trigger = "1"
+layer = "nav"
+script = "tap b"
+
+[[macros]]
+id = "game-2"
+trigger = "2"
+layer = "game"
+script = "tap c"
+"#,
+    )
+    .unwrap()
+}
+
+fn down(state: &mut LayerState, key: &str) -> LayerEvent {
+    state.key_down(&Chord::parse(key))
+}
+
+#[test]
+fn momentary_and_toggle_layers() {
+    let mut state = LayerState::new(&config());
+    assert_eq!(down(&mut state, "a"), LayerEvent::Pass);
+
+    assert_eq!(down(&mut state, "space"), LayerEvent::Active(Some("nav".into())));
+    assert_eq!(down(&mut state, "space"), LayerEvent::Consume, "자동 반복");
+    assert_eq!(state.key_up("space"), LayerEvent::Active(None));
+
+    assert_eq!(down(&mut state, "f9"), LayerEvent::Active(Some("game".into())));
+    assert_eq!(state.key_up("f9"), LayerEvent::Consume);
+    // 누르고 있는 레이어가 전환한 레이어 위에 온다
+    assert_eq!(down(&mut state, "space"), LayerEvent::Active(Some("nav".into())));
+    assert_eq!(state.key_up("space"), LayerEvent::Active(Some("game".into())));
+    down(&mut state, "f9");
+    assert_eq!(state.active(), None);
+}
+
+#[test]
+fn toggle_key_disables_layer_keys() {
+    let mut state = LayerState::new(&config());
+    assert_eq!(down(&mut state, "f8"), LayerEvent::Enabled(false));
+    assert_eq!(down(&mut state, "f8"), LayerEvent::Consume, "자동 반복으로 다시 켜지지 않는다");
+    assert_eq!(state.key_up("f8"), LayerEvent::Consume);
+    assert_eq!(down(&mut state, "space"), LayerEvent::Pass);
+    assert_eq!(state.key_up("space"), LayerEvent::Pass);
+
+    state.key_up("f8");
+    assert_eq!(down(&mut state, "f8"), LayerEvent::Enabled(true));
+    assert!(state.is_enabled());
+}
+
+#[test]
+fn toggle_and_layer_keys_ignore_held_modifiers() {
+    let mut state = LayerState::new(&config());
+    assert_eq!(down(&mut state, "shift+f8"), LayerEvent::Enabled(false));
+    state.key_up("f8");
+    down(&mut state, "f8");
+    assert_eq!(down(&mut state, "ctrl+space"), LayerEvent::Active(Some("nav".into())));
+    assert_eq!(state.key_up("space"), LayerEvent::Active(None));
+}
+
+#[test]
+fn layer_view_overrides_base_triggers_and_remaps() {
+    let config = config();
+    let base = layer::view(&config, None);
+    let enabled: Vec<bool> = base.macros.iter().map(|m| m.enabled).collect();
+    assert_eq!(enabled, [true, false, false]);
+
+    let nav = layer::view(&config, Some("nav"));
+    let enabled: Vec<bool> = nav.macros.iter().map(|m| m.enabled).collect();
+    assert_eq!(enabled, [false, true, false]);
+    let mut recognizer = GestureRecognizer::new(&nav);
+    assert_eq!(recognizer.key_down(&Chord::parse("1"), 0).fire, [1]);
+
+    let mut remapper = Remapper::new(&nav);
+    assert_eq!(remapper.key_down("h").as_deref(), Some("left"));
+    assert_eq!(remapper.key_down("capslock").as_deref(), Some("esc"));
+    assert_eq!(Remapper::new(&base).key_down("h"), None);
+
+    // 레이어가 다르면 같은 트리거도 겹치지 않는다
+    assert!(config.trigger_conflicts().iter().all(|c| c.kind != ConflictKind::Duplicate));
+}
+
+#[test]
+fn layer_definitions_are_checked() {
+    let mut config = config();
+    config.layers[1].key = "f8".into();
+    config.layers.push(config.layers[0].clone());
+    config.macros[2].layer = "missing".into();
+    config.macros.push(shared::Macro::new("space g"));
+    let errors: Vec<String> = layer::check(&config).iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        errors,
+        [
+            "레이어 nav의 키 space가 space g와 겹칩니다",
+            "레이어 game의 키 f8가 토글 키와 겹칩니다",
+            "레이어 이름이 겹칩니다: nav",
+            "레이어 nav의 키 space가 레이어 nav와 겹칩니다",
+            "레이어 nav의 키 space가 space g와 겹칩니다",
+            "2: 정의되지 않은 레이어입니다: missing",
+        ]
+    );
+    assert!(matches!(layer::check(&config)[0], LayerError::KeyTaken { .. }));
+}
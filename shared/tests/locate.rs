@@ -0,0 +1,71 @@
+use shared::config::{locate_in, ConfigSource, CONFIG_FILE_NAME};
+use std::path::{Path, PathBuf};
+
+fn exists_in(files: &[PathBuf]) -> impl Fn(&Path) -> bool + '_ {
+    move |path| files.iter().any(|f| f == path)
+}
+
+#[test]
+fn explicit_flag_wins_even_if_missing() {
+    let exe = PathBuf::from("/opt/keym");
+    let files = vec![exe.join(CONFIG_FILE_NAME)];
+
+    let found = locate_in(
+        Some(Path::new("/tmp/mine.toml")),
+        Some(Path::new("/tmp/env.toml")),
+        Some(&exe),
+        None,
+        exists_in(&files),
+    );
+
+    assert_eq!(found.path, PathBuf::from("/tmp/mine.toml"));
+    assert_eq!(found.source, ConfigSource::Flag);
+    assert!(!found.exists);
+}
+
+#[test]
+fn env_var_comes_before_directories() {
+    let exe = PathBuf::from("/opt/keym");
+    let files = vec![exe.join(CONFIG_FILE_NAME), PathBuf::from("/tmp/env.toml")];
+
+    let found = locate_in(None, Some(Path::new("/tmp/env.toml")), Some(&exe), None, exists_in(&files));
+
+    assert_eq!(found.source, ConfigSource::Env);
+    assert!(found.exists);
+}
+
+#[test]
+fn exe_dir_is_preferred_when_present() {
+    let exe = PathBuf::from("/opt/keym");
+    let user = PathBuf::from("/home/me/.config/keym");
+    let files = vec![exe.join(CONFIG_FILE_NAME), user.join(CONFIG_FILE_NAME)];
+
+    let found = locate_in(None, None, Some(&exe), Some(&user), exists_in(&files));
+
+    assert_eq!(found.path, exe.join(CONFIG_FILE_NAME));
+    assert_eq!(found.source, ConfigSource::ExeDir);
+}
+
+#[test]
+fn user_dir_is_used_when_exe_dir_has_no_config() {
+    let exe = PathBuf::from("/opt/keym");
+    let user = PathBuf::from("/home/me/.config/keym");
+    let files = vec![user.join(CONFIG_FILE_NAME)];
+
+    let found = locate_in(None, None, Some(&exe), Some(&user), exists_in(&files));
+
+    assert_eq!(found.source, ConfigSource::UserDir);
+    assert!(found.exists);
+}
+
+#[test]
+fn new_config_goes_to_user_dir() {
+    let exe = PathBuf::from("/opt/keym");
+    let user = PathBuf::from("/home/me/.config/keym");
+
+    let found = locate_in(None, None, Some(&exe), Some(&user), exists_in(&[]));
+
+    assert_eq!(found.path, user.join(CONFIG_FILE_NAME));
+    assert_eq!(found.source, ConfigSource::UserDir);
+    assert!(!found.exists);
+}
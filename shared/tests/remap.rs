@@ -0,0 +1,52 @@
+use shared::remap::{self, RemapError, Remapper};
+use shared::{Macro, MacroConfig};
+
+fn config_of(remaps: &[(&str, &str)]) -> MacroConfig {
+    let remap = remaps.iter().map(|&(k, t)| (k.to_string(), t.to_string())).collect();
+    MacroConfig { remap, ..MacroConfig::default() }
+}
+
+#[test]
+fn holds_are_mirrored() {
+    let mut remapper = Remapper::new(&config_of(&[("capslock", "esc")]));
+    assert_eq!(remapper.key_down("capslock").as_deref(), Some("esc"));
+    // 자동 반복
+    assert_eq!(remapper.key_down("capslock").as_deref(), Some("esc"));
+    assert_eq!(remapper.key_up("capslock").as_deref(), Some("esc"));
+    assert_eq!(remapper.key_up("capslock"), None);
+
+    assert_eq!(remapper.key_down("a"), None);
+    assert_eq!(remapper.key_up("a"), None);
+}
+
+#[test]
+fn swapped_keys_do_not_chain() {
+    let mut remapper = Remapper::new(&config_of(&[("a", "b"), ("b", "a")]));
+    assert_eq!(remapper.key_down("a").as_deref(), Some("b"));
+    assert_eq!(remapper.key_down("b").as_deref(), Some("a"));
+    assert_eq!(remapper.key_up("a").as_deref(), Some("b"));
+    assert_eq!(remapper.key_up("b").as_deref(), Some("a"));
+}
+
+#[test]
+fn remap_table_round_trips_and_is_checked() {
+    let mut config = MacroConfig::parse(
+        "[remap]\ncapslock = \"esc\"\nf13 = \"a\"\n\n[[macros]]\ntrigger = \"ctrl+capslock\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.remap["capslock"], "esc");
+    let saved = toml::to_string(&config).unwrap();
+    assert!(saved.contains("[remap]"), "{}", saved);
+
+    config.macros.push(Macro::new("esc"));
+    assert_eq!(
+        remap::check(&config),
+        [
+            RemapError::Trigger { key: "capslock".into(), target: "esc".into(), name: "ctrl+capslock".into() },
+            RemapError::UnknownKey { key: "f13".into() },
+        ]
+    );
+    // 보낼 수 없는 항목은 쓰지 않는다
+    let mut remapper = Remapper::new(&config);
+    assert_eq!(remapper.key_down("f13"), None);
+}
@@ -0,0 +1,42 @@
+use shared::running::{RunningMacros, StartError, MAX_CHAIN_DEPTH};
+
+#[test]
+fn triggers_are_ignored_while_running() {
+    let mut runs = RunningMacros::new(false);
+    assert_eq!(runs.start(Some(0), false), Ok(true));
+    assert_eq!(runs.start(Some(1), false), Err(StartError::Busy));
+    assert_eq!(runs.start(None, false), Err(StartError::Busy), "테스트 재생도 기다리지 않는다");
+    assert!(runs.finish(Some(0)));
+    assert_eq!(runs.start(Some(1), false), Ok(true));
+}
+
+#[test]
+fn injected_keys_start_nothing_unless_chained() {
+    let mut runs = RunningMacros::new(false);
+    assert_eq!(runs.start(Some(0), true), Err(StartError::Injected));
+    assert!(!runs.is_running());
+
+    let mut runs = RunningMacros::new(true);
+    assert_eq!(runs.start(Some(0), false), Ok(true));
+    // 체인으로 시작한 매크로는 부모와 함께 돈다. 처음 시작이 아니므로 취소 요청을 지우지 않는다
+    assert_eq!(runs.start(Some(1), true), Ok(false));
+    // 자식이 끝나도 부모는 아직 실행 중
+    assert!(!runs.finish(Some(1)));
+    assert!(runs.is_running());
+    assert!(runs.finish(Some(0)));
+}
+
+#[test]
+fn chains_stop_at_cycles_and_depth() {
+    let mut runs = RunningMacros::new(true);
+    runs.start(Some(0), false).unwrap();
+    runs.start(Some(1), true).unwrap();
+    // a → b → a
+    assert_eq!(runs.start(Some(0), true), Err(StartError::Cycle));
+    assert_eq!(runs.start(Some(1), true), Err(StartError::Cycle));
+
+    for index in 2..MAX_CHAIN_DEPTH {
+        assert_eq!(runs.start(Some(index), true), Ok(false));
+    }
+    assert_eq!(runs.start(Some(MAX_CHAIN_DEPTH), true), Err(StartError::TooDeep));
+}
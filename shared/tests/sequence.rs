@@ -0,0 +1,114 @@
+use shared::config::ConflictKind;
+use shared::gesture::GestureRecognizer;
+use shared::sequence::{SequenceDecision, SequenceMatcher};
+use shared::trigger::Chord;
+
+mod common;
+use common::config_of_triggers;
+
+fn down(matcher: &mut SequenceMatcher, key: &str, now: u64) -> SequenceDecision {
+    matcher.key_down(&Chord::parse(key), now)
+}
+
+fn names(chords: &[Chord]) -> Vec<String> {
+    chords.iter().map(Chord::to_string).collect()
+}
+
+#[test]
+fn fires_when_steps_arrive_in_time() {
+    let config = config_of_triggers(&["1", "f12 g 1"]);
+    let mut matcher = SequenceMatcher::new(&config);
+    assert!(down(&mut matcher, "f12", 0).consume);
+    assert!(matcher.key_up("f12"));
+    assert!(down(&mut matcher, "g", 400).consume);
+    matcher.key_up("g");
+    let decision = down(&mut matcher, "1", 900);
+    assert_eq!(decision.fire, Some(1));
+    assert!(decision.replay.is_empty());
+
+    // 단일 트리거는 제스처 인식기가 맡는다
+    assert!(!GestureRecognizer::new(&config).is_bound("f12 g 1"));
+}
+
+#[test]
+fn timeout_replays_swallowed_keys() {
+    let mut config = config_of_triggers(&["f12 g 1"]);
+    config.sequence_timeout_ms = 500;
+    let mut matcher = SequenceMatcher::new(&config);
+    down(&mut matcher, "f12", 0);
+    down(&mut matcher, "g", 300);
+    matcher.key_up("f12");
+    matcher.key_up("g");
+    assert!(matcher.poll(800).is_empty());
+    assert_eq!(names(&matcher.poll(801)), ["f12", "g"]);
+
+    // 시간이 지난 뒤의 키는 새 순서로 시작한다
+    down(&mut matcher, "f12", 1000);
+    matcher.key_up("f12");
+    let decision = down(&mut matcher, "1", 2000);
+    assert_eq!(names(&decision.replay), ["f12", "1"]);
+    assert!(decision.consume);
+}
+
+#[test]
+fn mismatch_replays_buffer_and_restarts() {
+    let config = config_of_triggers(&["f12 g 1"]);
+    let mut matcher = SequenceMatcher::new(&config);
+    down(&mut matcher, "f12", 0);
+    let decision = down(&mut matcher, "x", 10);
+    assert_eq!(names(&decision.replay), ["f12", "x"]);
+    assert!(decision.consume);
+    assert!(matcher.key_up("f12"));
+    assert!(matcher.key_up("x"), "돌려보낸 키를 뗄 때도 삼킨다");
+
+    // 자동 반복은 삼키고 순서를 끊지 않는다
+    down(&mut matcher, "f12", 20);
+    let decision = down(&mut matcher, "f12", 30);
+    assert!(decision.consume);
+    assert!(decision.replay.is_empty());
+    matcher.key_up("f12");
+
+    // 끊긴 키가 새 순서의 시작이면 기다린다
+    let decision = down(&mut matcher, "f12", 40);
+    assert_eq!(names(&decision.replay), ["f12"]);
+    matcher.key_up("f12");
+    assert_eq!(down(&mut matcher, "g", 50).fire, None);
+    assert_eq!(down(&mut matcher, "1", 60).fire, Some(0));
+
+    // 순서와 상관없는 키는 그대로 지나간다
+    assert_eq!(down(&mut matcher, "a", 70), SequenceDecision::default());
+}
+
+#[test]
+fn replay_keeps_held_modifiers() {
+    let mut config = config_of_triggers(&["ctrl+f12 g"]);
+    config.sequence_timeout_ms = 500;
+    let mut matcher = SequenceMatcher::new(&config);
+    down(&mut matcher, "ctrl+f12", 0);
+    let decision = down(&mut matcher, "shift+x", 10);
+    assert_eq!(decision.replay, [Chord::parse("ctrl+f12"), Chord::parse("shift+x")]);
+
+    matcher.key_up("f12");
+    matcher.key_up("x");
+    down(&mut matcher, "ctrl+f12", 20);
+    assert_eq!(matcher.poll(600), [Chord::parse("ctrl+f12")]);
+}
+
+#[test]
+fn ambiguous_prefixes_are_reported() {
+    let config = config_of_triggers(&["f12 g 1", "f12 g", "f12", "f12  g"]);
+    let found: Vec<(ConflictKind, Vec<String>)> = config
+        .trigger_conflicts()
+        .into_iter()
+        .map(|c| (c.kind, c.macro_ids))
+        .collect();
+    let ids: Vec<String> = config.macros.iter().map(|m| m.id.clone()).collect();
+    assert!(found.contains(&(ConflictKind::AmbiguousSequence, vec![ids[0].clone(), ids[1].clone()])));
+    assert!(found.contains(&(ConflictKind::AmbiguousSequence, vec![ids[1].clone(), ids[2].clone()])));
+    assert!(found.contains(&(ConflictKind::Duplicate, vec![ids[3].clone(), ids[1].clone()])));
+
+    // 짧은 순서가 먼저 맞아 실행된다
+    let mut matcher = SequenceMatcher::new(&config);
+    down(&mut matcher, "f12", 0);
+    assert_eq!(down(&mut matcher, "g", 10).fire, Some(1));
+}
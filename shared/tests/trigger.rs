@@ -0,0 +1,30 @@
+use shared::trigger::{self, Chord};
+
+#[test]
+fn chords_are_parsed_and_normalized() {
+    assert_eq!(Chord::parse("shift+ctrl+1"), Chord::new(trigger::CTRL | trigger::SHIFT, "1"));
+    assert_eq!(trigger::normalize("shift + ctrl+1"), "ctrl+shift+1");
+    assert_eq!(trigger::normalize("num+"), "num+");
+    assert_eq!(trigger::normalize("ctrl+num+"), "ctrl+num+");
+    assert_eq!(trigger::normalize("shift"), "shift");
+    assert_eq!(Chord::new(trigger::SHIFT, "shift").to_string(), "shift");
+    assert_eq!(Chord::parse("shift+ctrl+1").modifier_keys(), ["ctrl", "shift"]);
+}
+
+#[test]
+fn steps_parse_around_plus() {
+    let steps: Vec<String> = trigger::steps("ctrl + 1 g").iter().map(Chord::to_string).collect();
+    assert_eq!(steps, ["ctrl+1", "g"]);
+    let steps: Vec<String> = trigger::steps("num+ g").iter().map(Chord::to_string).collect();
+    assert_eq!(steps, ["num+", "g"]);
+    assert_eq!(trigger::normalize("f12  g   shift + 1"), "f12 g shift+1");
+    assert!(!trigger::is_sequence("ctrl+alt+f"));
+}
+
+#[test]
+fn plain_triggers_also_match_with_modifiers_held() {
+    assert_eq!(Chord::parse("1").names(), ["1"]);
+    // 조합 표기가 먼저라 "ctrl+1" 트리거가 "1"보다 우선한다
+    assert_eq!(Chord::parse("ctrl+1").names(), ["ctrl+1", "1"]);
+    assert_eq!(Chord::new(trigger::CTRL, "ctrl").names(), ["ctrl"]);
+}
@@ -1,4 +1,5 @@
 const { invoke } = window.__TAURI__.tauri;
+const { listen } = window.__TAURI__.event;
 
 let config = null;
 let selectedMacroIndex = 0;
@@ -11,9 +12,10 @@ async function init() {
     try {
         availableKeys = await invoke('get_available_keys');
         config = await invoke('load_config');
+        const location = await invoke('get_config_location');
         populateKeySelects();
         refreshUI();
-        setStatus('로드 완료');
+        setStatus(location.exists ? `로드 완료: ${location.path}` : `새 설정: ${location.path}`);
     } catch (error) {
         setStatus(`오류: ${error}`);
     }
@@ -38,6 +40,36 @@ function refreshUI() {
     refreshMacroTabs();
     refreshMacroSettings();
     refreshActionsTable();
+    highlightConflicts();
+}
+
+// 트리거 문제가 있는 매크로 탭 표시 (마우스를 올리면 이유)
+async function highlightConflicts() {
+    try {
+        const conflicts = await invoke('analyze_conflicts', { config });
+        const tabs = document.getElementById('macroTabs').children;
+        config.macros.forEach((macro, index) => {
+            const messages = conflicts
+                .filter(c => c.macro_ids[0] === macro.id)
+                .map(c => conflictMessage(c));
+            tabs[index]?.classList.toggle('conflict', messages.length > 0);
+            if (tabs[index]) tabs[index].title = messages.join('\n');
+        });
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+function conflictMessage(conflict) {
+    const [, other] = conflict.names;
+    switch (conflict.kind) {
+        case 'duplicate': return `트리거 ${conflict.trigger}가 ${other}와 겹칩니다`;
+        case 'toggle_key': return `트리거 ${conflict.trigger}가 토글 키와 같습니다`;
+        case 'shadowed': return `${other}의 트리거가 ${conflict.trigger}를 먼저 가로챕니다`;
+        case 'self_trigger': return `자기 트리거 ${conflict.trigger}를 눌러 다시 실행될 수 있습니다`;
+        case 'ambiguous_sequence': return `순서 트리거 ${conflict.trigger}의 앞부분이 ${other}의 트리거와 겹칩니다`;
+        default: return conflict.kind;
+    }
 }
 
 // 매크로 탭 새로고침
@@ -51,7 +83,11 @@ function refreshMacroTabs() {
         if (index === selectedMacroIndex) {
             tab.classList.add('active');
         }
-        tab.textContent = `매크로 ${index + 1} [${macro.trigger}] (${macro.actions.length}개)`;
+        if (macro.enabled === false) {
+            tab.classList.add('disabled');
+        }
+        const label = macro.name || `매크로 ${index + 1}`;
+        tab.textContent = `${label} [${macro.trigger}] (${macro.actions.length}개)`;
         tab.onclick = () => selectMacro(index);
         container.appendChild(tab);
     });
@@ -63,9 +99,35 @@ function refreshMacroSettings() {
         const macro = config.macros[selectedMacroIndex];
         document.getElementById('triggerKey').value = macro.trigger;
         document.getElementById('mode').value = macro.mode;
+        document.getElementById('macroName').value = macro.name || '';
+        document.getElementById('macroDescription').value = macro.description || '';
+        document.getElementById('macroTags').value = (macro.tags || []).join(', ');
+        document.getElementById('macroEnabled').checked = macro.enabled !== false;
+        const [gestureKind, gestureMs] = gestureFields(macro.gesture);
+        document.getElementById('macroGesture').value = gestureKind;
+        document.getElementById('macroGestureMs').value = gestureMs;
+        document.getElementById('macroPassthrough').value = macro.passthrough || 'off';
+        document.getElementById('macroLayer').value = macro.layer || '';
     }
 }
 
+// "press", "release" 또는 { long_press: 500 } → [종류, ms]
+function gestureFields(gesture) {
+    if (!gesture || typeof gesture === 'string') return [gesture || 'press', ''];
+    const [kind, ms] = Object.entries(gesture)[0];
+    return [kind, ms];
+}
+
+function gestureValue(kind, ms) {
+    if (kind === 'press' || kind === 'release') return kind;
+    return { [kind]: parseInt(ms) || 300 };
+}
+
+// 선택된 매크로 id
+function selectedMacroId() {
+    return config.macros[selectedMacroIndex].id;
+}
+
 // 액션 테이블 새로고침
 function refreshActionsTable() {
     const tbody = document.getElementById('actionsBody');
@@ -83,7 +145,7 @@ function refreshActionsTable() {
         
         row.innerHTML = `
             <td>${action.hold_ms}</td>
-            <td>${action.key}</td>
+            <td>${actionLabel(action)}</td>
             <td>${action.delay_ms}</td>
             <td>
                 <button class="action-btn" onclick="editAction(${index})">수정</button>
@@ -94,6 +156,25 @@ function refreshActionsTable() {
     });
 }
 
+// 액션 표시 이름 (호출, 묶음, 누르기/떼기, 반복)
+function actionLabel(action) {
+    let label;
+    if (action.group && action.group.length > 0) {
+        label = `묶음 (${action.group.length}개)`;
+    } else if (action.call) {
+        label = `▶ ${action.call}`;
+    } else if (action.press === 'down') {
+        label = `${action.key} ↓`;
+    } else if (action.press === 'up') {
+        label = `${action.key} ↑`;
+    } else {
+        label = action.key;
+    }
+    return action.repeat !== undefined && action.repeat !== null
+        ? `${label} ×${action.repeat}`
+        : label;
+}
+
 // 매크로 선택
 function selectMacro(index) {
     selectedMacroIndex = index;
@@ -126,12 +207,32 @@ async function saveMacro() {
     try {
         const trigger = document.getElementById('triggerKey').value;
         const mode = parseInt(document.getElementById('mode').value);
+        const name = document.getElementById('macroName').value.trim();
+        const description = document.getElementById('macroDescription').value;
+        const tags = document.getElementById('macroTags').value
+            .split(',')
+            .map(tag => tag.trim())
+            .filter(tag => tag.length > 0);
+        const enabled = document.getElementById('macroEnabled').checked;
+        const gesture = gestureValue(
+            document.getElementById('macroGesture').value,
+            document.getElementById('macroGestureMs').value
+        );
+        const passthrough = document.getElementById('macroPassthrough').value;
+        const layer = document.getElementById('macroLayer').value.trim();
         
         config = await invoke('update_macro', {
             config,
-            index: selectedMacroIndex,
+            id: selectedMacroId(),
             trigger,
-            mode
+            mode,
+            name,
+            description,
+            tags,
+            enabled,
+            gesture,
+            passthrough,
+            layer
         });
         
         refreshUI();
@@ -148,7 +249,7 @@ async function deleteMacro() {
     try {
         config = await invoke('delete_macro', {
             config,
-            index: selectedMacroIndex
+            id: selectedMacroId()
         });
         
         if (config.macros.length === 0) {
@@ -175,6 +276,228 @@ async function saveAll() {
     }
 }
 
+// 백업에서 복원
+async function restoreBackup() {
+    try {
+        const backups = await invoke('list_backups');
+        if (backups.length === 0) {
+            setStatus('백업이 없습니다');
+            return;
+        }
+        
+        const list = backups
+            .map(b => `${b.index}: ${new Date(b.modified_secs * 1000).toLocaleString()} (${b.size} bytes)`)
+            .join('\n');
+        const answer = prompt(`복원할 백업 번호를 입력하세요 (1이 가장 최근)\n${list}`);
+        if (answer === null) return;
+        
+        config = await invoke('restore_backup', { index: parseInt(answer) });
+        selectedMacroIndex = 0;
+        selectedActionIndex = null;
+        refreshUI();
+        setStatus(`백업 ${answer} 복원 완료`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 다른 매크로 호출 액션 추가
+async function addCallAction() {
+    if (config.macros.length < 2) {
+        setStatus('호출할 다른 매크로가 없습니다');
+        return;
+    }
+    
+    const current = selectedMacroId();
+    const list = config.macros
+        .filter(m => m.id !== current)
+        .map(m => `${m.id}: ${m.name || m.trigger}`)
+        .join('\n');
+    const target = prompt(`호출할 매크로 id를 입력하세요\n${list}`);
+    if (target === null || target.trim() === '') return;
+    
+    try {
+        config = await invoke('add_call_action', {
+            config,
+            macroId: current,
+            target: target.trim(),
+            delayMs: 0
+        });
+        refreshUI();
+        setStatus(`호출 액션 추가됨: ${target.trim()}`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 다른 도구용 스크립트로 내보내기
+async function exportConfig(format) {
+    try {
+        const path = await invoke('export_config', { config, format });
+        setStatus(`내보냄: ${path}`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 선택한 매크로 하나만 셸 스크립트로 내보내기
+async function exportMacro(format) {
+    try {
+        const path = await invoke('export_macro', { config, id: selectedMacroId(), format });
+        setStatus(`내보냄: ${path}`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// AutoHotkey 스크립트 가져오기. 옮기지 못한 줄은 줄 번호와 함께 알려준다
+async function importAhk() {
+    const path = prompt('가져올 AutoHotkey 스크립트 경로');
+    if (!path) return;
+    
+    try {
+        const before = config.macros.length;
+        const result = await invoke('import_ahk', { config, path });
+        config = result.config;
+        refreshUI();
+        
+        const added = config.macros.length - before;
+        if (result.unsupported.length > 0) {
+            const lines = result.unsupported.map(u => `${u.line}줄: ${u.text} (${u.reason})`);
+            alert(`건너뛴 줄 ${result.unsupported.length}개\n\n${lines.join('\n')}`);
+        }
+        setStatus(`매크로 ${added}개를 가져왔습니다`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 선택한 매크로를 JSON 번들로 내보내기 (다른 매크로 id를 쉼표로 더할 수 있다)
+async function exportBundle() {
+    const selected = selectedMacroIndex < config.macros.length ? selectedMacroId() : '';
+    const input = prompt('내보낼 매크로 id (쉼표로 구분)', selected);
+    if (!input) return;
+    const path = prompt('저장할 번들 경로', 'keym-macros.json');
+    if (!path) return;
+    
+    try {
+        const ids = input.split(',').map(id => id.trim()).filter(id => id);
+        const count = await invoke('export_macro_bundle', { config, ids, path });
+        setStatus(`매크로 ${count}개를 내보냈습니다: ${path}`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// JSON 번들 가져오기. 트리거가 겹치면 하나씩 처리 방법을 묻는다
+async function importBundle() {
+    const path = prompt('가져올 번들 경로');
+    if (!path) return;
+    
+    try {
+        const { bundle, conflicts } = await invoke('read_macro_bundle', { config, path });
+        const resolutions = {};
+        for (const conflict of conflicts) {
+            const answer = prompt(
+                `트리거 '${conflict.trigger}'가 겹칩니다: ${conflict.name} ↔ ${conflict.in_bundle ? '번들 안의' : '기존'} ${conflict.existing_name}\n` +
+                `skip = 건너뛰기, replace = 겹치는 매크로 덮어쓰기, 그 밖의 입력 = 새 트리거`,
+                'skip'
+            );
+            if (answer === null) {
+                setStatus('가져오기를 취소했습니다');
+                return;
+            }
+            const choice = answer.trim();
+            if (choice === 'skip' || choice === 'replace') {
+                resolutions[conflict.id] = { action: choice };
+            } else {
+                resolutions[conflict.id] = { action: 'rename', trigger: choice };
+            }
+        }
+        
+        config = await invoke('import_macro_bundle', { config, bundle, resolutions });
+        refreshUI();
+        setStatus(`번들을 가져왔습니다 (매크로 ${bundle.macros.length}개, 충돌 ${conflicts.length}개)`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 키 바꾸기 표 편집 ("capslock=esc, a=b, b=a")
+async function editRemaps() {
+    const current = Object.entries(config.remap || {}).map(([key, target]) => `${key}=${target}`).join(', ');
+    const input = prompt('키 바꾸기 (원래 키=보낼 키, 쉼표로 구분)', current);
+    if (input === null) return;
+    
+    try {
+        const remap = {};
+        for (const pair of input.split(',').map(p => p.trim()).filter(p => p)) {
+            const [key, target] = pair.split('=').map(k => k.trim());
+            if (!key || !target) throw `잘못된 항목: ${pair}`;
+            remap[key] = target;
+        }
+        config = await invoke('update_remaps', { config, remap });
+        setStatus(`키 바꾸기 ${Object.keys(remap).length}개 (저장해야 적용됩니다)`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 레이어 편집 ("nav=space, game=f9:toggle"). 레이어별 키 바꾸기는 그대로 둔다
+async function editLayers() {
+    const layers = config.layers || [];
+    const current = layers
+        .map(l => `${l.name}=${l.key}${l.mode === 'toggle' ? ':toggle' : ''}`)
+        .join(', ');
+    const input = prompt('레이어 (이름=키, 전환하려면 이름=키:toggle, 쉼표로 구분)', current);
+    if (input === null) return;
+    
+    try {
+        const updated = input.split(',').map(p => p.trim()).filter(p => p).map(pair => {
+            const [name, rest] = pair.split('=').map(k => k.trim());
+            if (!name || !rest) throw `잘못된 항목: ${pair}`;
+            const [key, mode] = rest.split(':').map(k => k.trim());
+            const existing = layers.find(l => l.name === name);
+            return { name, key, mode: mode === 'toggle' ? 'toggle' : 'momentary', remap: existing ? existing.remap || {} : {} };
+        });
+        config = await invoke('update_layers', { config, layers: updated });
+        setStatus(`레이어 ${updated.length}개 (저장해야 적용됩니다)`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 문법으로 편집 (현재 액션을 내보낸 뒤 고친 내용을 다시 가져온다)
+async function editMacroScript() {
+    try {
+        const id = selectedMacroId();
+        const current = await invoke('export_macro_script', { config, id });
+        const script = prompt('매크로 문법 (예: tap a 30ms; wait 100ms; repeat 5 { tap e })', current);
+        if (script === null || script === current) return;
+        
+        config = await invoke('import_macro_script', { config, id, script });
+        selectedActionIndex = null;
+        refreshUI();
+        setStatus('문법에서 액션을 가져왔습니다');
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 펼친 결과 미리보기 (call, 반복, 매개변수 적용)
+async function previewMacro() {
+    try {
+        const expanded = await invoke('preview_macro', {
+            config,
+            id: selectedMacroId()
+        });
+        const keys = expanded.actions.map(a => a.key).join(' ');
+        setStatus(`펼친 결과 (${expanded.actions.length}개): ${keys}`);
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
 // 액션 추가 모달 열기
 function openAddActionModal() {
     if (config.macros.length === 0) {
@@ -213,7 +536,7 @@ async function saveAction() {
         if (editingAction) {
             config = await invoke('update_action', {
                 config,
-                macroIndex: selectedMacroIndex,
+                macroId: selectedMacroId(),
                 actionIndex: selectedActionIndex,
                 key,
                 holdMs,
@@ -223,7 +546,7 @@ async function saveAction() {
         } else {
             config = await invoke('add_action', {
                 config,
-                macroIndex: selectedMacroIndex,
+                macroId: selectedMacroId(),
                 key,
                 holdMs,
                 delayMs
@@ -243,7 +566,7 @@ async function deleteAction(index) {
     try {
         config = await invoke('delete_action', {
             config,
-            macroIndex: selectedMacroIndex,
+            macroId: selectedMacroId(),
             actionIndex: index
         });
         
@@ -273,7 +596,7 @@ async function moveAction(direction) {
     try {
         config = await invoke('move_action', {
             config,
-            macroIndex: selectedMacroIndex,
+            macroId: selectedMacroId(),
             fromIndex: selectedActionIndex,
             toIndex: newIndex
         });
@@ -286,6 +609,55 @@ async function moveAction(direction) {
     }
 }
 
+// 테스트 재생 (저장하지 않은 매크로를 Runner로 전송)
+async function testPlay() {
+    if (selectedMacroIndex >= config.macros.length) return;
+    
+    try {
+        await invoke('test_play_macro', {
+            config,
+            id: selectedMacroId(),
+            countdownMs: 3000
+        });
+        setStatus('테스트 재생 요청됨');
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 테스트 재생 취소
+async function cancelTestPlay() {
+    try {
+        await invoke('cancel_test_play');
+    } catch (error) {
+        setStatus(`오류: ${error}`);
+    }
+}
+
+// 테스트 재생 진행 상황
+listen('test-play', ({ payload }) => {
+    switch (payload.type) {
+        case 'countdown':
+            setStatus(`테스트 재생 ${Math.ceil(payload.remaining_ms / 1000)}초 전`);
+            break;
+        case 'started':
+            setStatus(`테스트 재생 시작 (${payload.total}개 액션)`);
+            break;
+        case 'progress':
+            setStatus(`테스트 재생 중: ${payload.done}/${payload.total}`);
+            break;
+        case 'finished':
+            setStatus('테스트 재생 완료');
+            break;
+        case 'cancelled':
+            setStatus('테스트 재생 취소됨');
+            break;
+        case 'error':
+            setStatus(`오류: ${payload.message}`);
+            break;
+    }
+});
+
 // 모달 닫기
 function closeModal() {
     document.getElementById('actionModal').classList.remove('active');
@@ -301,11 +673,27 @@ document.getElementById('addMacroBtn').onclick = addMacro;
 document.getElementById('saveMacroBtn').onclick = saveMacro;
 document.getElementById('deleteMacroBtn').onclick = deleteMacro;
 document.getElementById('saveAllBtn').onclick = saveAll;
+document.getElementById('restoreBackupBtn').onclick = restoreBackup;
 document.getElementById('addActionBtn').onclick = openAddActionModal;
+document.getElementById('addCallBtn').onclick = addCallAction;
+document.getElementById('previewMacroBtn').onclick = previewMacro;
+document.getElementById('editScriptBtn').onclick = editMacroScript;
+document.getElementById('exportAhkBtn').onclick = () => exportConfig('ahk');
+document.getElementById('exportXdotoolBtn').onclick = () => exportConfig('xdotool');
+document.getElementById('exportYdotoolBtn').onclick = () => exportConfig('ydotool');
+document.getElementById('exportMacroXdotoolBtn').onclick = () => exportMacro('xdotool');
+document.getElementById('exportMacroYdotoolBtn').onclick = () => exportMacro('ydotool');
+document.getElementById('importAhkBtn').onclick = importAhk;
+document.getElementById('exportBundleBtn').onclick = exportBundle;
+document.getElementById('importBundleBtn').onclick = importBundle;
+document.getElementById('editRemapsBtn').onclick = editRemaps;
+document.getElementById('editLayersBtn').onclick = editLayers;
 document.getElementById('moveUpBtn').onclick = () => moveAction('up');
 document.getElementById('moveDownBtn').onclick = () => moveAction('down');
 document.getElementById('modalSaveBtn').onclick = saveAction;
 document.getElementById('modalCancelBtn').onclick = closeModal;
+document.getElementById('testPlayBtn').onclick = testPlay;
+document.getElementById('cancelTestPlayBtn').onclick = cancelTestPlay;
 
 // 앱 초기화
 init();
\ No newline at end of file